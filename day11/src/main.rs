@@ -1,4 +1,9 @@
-use std::{collections::HashMap, fs};
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::Write,
+};
 
 #[derive(Debug, PartialEq)]
 struct Answer {
@@ -25,14 +30,6 @@ struct Node {
     outputs: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
-struct NodeData {
-    node_name: String,
-    dac: bool,
-    fft: bool,
-    count: u64,
-}
-
 // impl Node {
 // }
 
@@ -55,185 +52,764 @@ fn parse_input(contents: &String) -> Vec<Node> {
     return nodes;
 }
 
-fn part1(contents: &String) -> Option<Answer> {
-    let nodes = parse_input(contents);
+// Problems with the devices, found before any counting starts
+#[derive(Debug, PartialEq, Clone)]
+enum GraphError {
+    MissingDevice { device: String },
+    UndefinedOutput { device: String, output: String },
+    Cycle { devices: Vec<String> },
+    Unreachable { start: String, devices: Vec<String> },
+}
 
-    let mut node_map = HashMap::new();
+impl GraphError {
+    // Devices nothing can get to don't change the counts, everything else
+    // means there's no answer
+    fn is_fatal(&self) -> bool {
+        return !matches!(self, GraphError::Unreachable { .. });
+    }
+}
 
-    for node in nodes.clone() {
-        node_map.insert(node.input.clone(), node);
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::MissingDevice { device } => {
+                write!(f, "there is no device called {device}")
+            }
+            GraphError::UndefinedOutput { device, output } => {
+                write!(f, "{device} outputs to {output}, which has no line of its own")
+            }
+            GraphError::Cycle { devices } => {
+                write!(f, "the devices loop round {}", devices.join(" -> "))
+            }
+            GraphError::Unreachable { start, devices } => {
+                write!(f, "{} can't be reached from {start}", devices.join(", "))
+            }
+        }
     }
+}
+
+// The devices, with each name swapped for an id in the order they're first
+// seen. Devices only named as an output, like "out", have no outputs and
+// aren't defined.
+#[derive(Debug, Clone)]
+struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    outputs: Vec<Vec<usize>>,
+    defined: Vec<bool>,
+}
+
+impl Graph {
+    fn new(nodes: &[Node]) -> Self {
+        let mut graph = Graph {
+            names: vec![],
+            ids: HashMap::new(),
+            outputs: vec![],
+            defined: vec![],
+        };
+
+        for node in nodes {
+            let id = graph.intern(&node.input);
+            graph.defined[id] = true;
+            for output in &node.outputs {
+                let output_id = graph.intern(output);
+                graph.outputs[id].push(output_id);
+            }
+        }
 
-    let first_node = node_map["you"].clone();
+        return graph;
+    }
 
-    let mut path_locations = vec![first_node];
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
 
-    let mut not_all_out = true;
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.outputs.push(vec![]);
+        self.defined.push(false);
+        return id;
+    }
 
-    let mut count = 0;
+    fn id(&self, name: &str) -> Option<usize> {
+        return self.ids.get(name).copied();
+    }
 
-    while not_all_out {
-        count += 1;
-        dbg!(count);
-        path_locations = find_next_nodes(node_map.clone(), path_locations);
+    fn name(&self, id: usize) -> &str {
+        return &self.names[id];
+    }
 
-        not_all_out = check_all_out(&path_locations)
+    fn len(&self) -> usize {
+        return self.names.len();
     }
 
-    let answer = path_locations.len() as u64;
-    return Some(Answer { answer });
-}
+    // Every device comes before the ones it outputs to. None if there's a
+    // loop, as then there's no order that works.
+    fn topological_order(&self) -> Option<Vec<usize>> {
+        let mut inputs = vec![0; self.len()];
+        for outputs in &self.outputs {
+            for output in outputs {
+                inputs[*output] += 1;
+            }
+        }
 
-fn check_all_out(path_locations: &Vec<Node>) -> bool {
-    for node in path_locations {
-        if node.input != "out" {
-            return true;
+        let mut ready: Vec<usize> = (0..self.len()).filter(|id| inputs[*id] == 0).collect();
+        let mut order = vec![];
+        while let Some(id) = ready.pop() {
+            order.push(id);
+            for output in &self.outputs[id] {
+                inputs[*output] -= 1;
+                if inputs[*output] == 0 {
+                    ready.push(*output);
+                }
+            }
         }
+
+        if order.len() != self.len() {
+            return None;
+        }
+        return Some(order);
     }
-    return false;
-}
 
-fn find_next_nodes(node_map: HashMap<String, Node>, path_locations: Vec<Node>) -> Vec<Node> {
-    // println!("Starting on new iteration with path_locations {path_locations:?}");
-    let mut new_path_locations = vec![];
-    for old_node in &path_locations {
-        if old_node.input == "out" {
-            new_path_locations.push(old_node.clone());
-            continue;
-        }
-        for node_name in old_node.outputs.clone() {
-            // println!("Node name is {node_name}");
-            if node_name == "out" {
-                new_path_locations.push(Node {
-                    input: "out".to_string(),
-                    outputs: vec![],
+    // Checks everything that would stop the paths from start to end being
+    // counted, returning every problem found
+    fn validate(&self, start: &str, end: &str) -> Result<(), Vec<GraphError>> {
+        let mut errors = vec![];
+
+        for device in [start, end] {
+            if self.id(device).is_none() {
+                errors.push(GraphError::MissingDevice {
+                    device: device.to_string(),
                 });
-            } else {
-                let new_node = node_map
-                    .get(&node_name)
-                    .expect("Expected a node here")
-                    .clone();
-                new_path_locations.push(new_node);
             }
         }
-    }
 
-    return new_path_locations;
-}
+        for (id, outputs) in self.outputs.iter().enumerate() {
+            for output in outputs {
+                if !self.defined[*output] && self.name(*output) != end {
+                    errors.push(GraphError::UndefinedOutput {
+                        device: self.name(id).to_string(),
+                        output: self.name(*output).to_string(),
+                    });
+                }
+            }
+        }
 
-// Part 1 attempted answers
-// 662: Correct!
+        if let Some(cycle) = self.find_cycle() {
+            errors.push(GraphError::Cycle {
+                devices: cycle.iter().map(|id| self.name(*id).to_string()).collect(),
+            });
+        }
 
-fn part2(contents: &String) -> Option<Answer> {
-    let nodes = parse_input(contents);
+        if let Some(start_id) = self.id(start) {
+            let reachable = self.reachable_from(start_id);
+            let unreachable: Vec<String> = (0..self.len())
+                .filter(|id| !reachable[*id])
+                .map(|id| self.name(id).to_string())
+                .collect();
+            if !unreachable.is_empty() {
+                errors.push(GraphError::Unreachable {
+                    start: start.to_string(),
+                    devices: unreachable,
+                });
+            }
+        }
 
-    let mut node_map = HashMap::new();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        return Err(errors);
+    }
 
-    for node in nodes.clone() {
-        node_map.insert(node.input.clone(), node);
+    fn reachable_from(&self, start: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.len()];
+        reachable[start] = true;
+        let mut to_visit = vec![start];
+        while let Some(id) = to_visit.pop() {
+            for output in &self.outputs[id] {
+                if !reachable[*output] {
+                    reachable[*output] = true;
+                    to_visit.push(*output);
+                }
+            }
+        }
+        return reachable;
     }
 
-    dbg!(&node_map);
+    // Depth first search, keeping the current path. Getting back to a device
+    // that's still on the path means there's a loop, which is returned
+    // starting and ending on that device.
+    fn find_cycle(&self) -> Option<Vec<usize>> {
+        let mut finished = vec![false; self.len()];
+        let mut on_path = vec![false; self.len()];
 
-    let mut path_data = vec![];
+        for root in 0..self.len() {
+            if finished[root] {
+                continue;
+            }
 
-    path_data.push(NodeData {
-        count: 1,
-        node_name: "svr".to_string(),
-        fft: false,
-        dac: false,
-    });
+            // Each device on the path, with the index of the next output to try
+            let mut path: Vec<(usize, usize)> = vec![(root, 0)];
+            on_path[root] = true;
+            while let Some((id, next)) = path.last_mut() {
+                let id = *id;
+                let Some(output) = self.outputs[id].get(*next).copied() else {
+                    finished[id] = true;
+                    on_path[id] = false;
+                    path.pop();
+                    continue;
+                };
+                *next += 1;
 
-    let mut not_all_out = true;
+                if on_path[output] {
+                    let loop_start = path
+                        .iter()
+                        .position(|(on, _)| *on == output)
+                        .expect("Expected the device to be on the path");
+                    let mut cycle: Vec<usize> =
+                        path[loop_start..].iter().map(|(on, _)| *on).collect();
+                    cycle.push(output);
+                    return Some(cycle);
+                }
+                if !finished[output] {
+                    on_path[output] = true;
+                    path.push((output, 0));
+                }
+            }
+        }
 
-    let mut count = 0;
+        return None;
+    }
 
-    while not_all_out {
-        count += 1;
-        dbg!(count);
-        dbg!(&path_data);
-        path_data = find_next_nodes_part2(node_map.clone(), path_data);
+    fn constraints(&self, must_visit: &[usize], must_avoid: &[usize]) -> Constraints {
+        let mut visit_bits = vec![0usize; self.len()];
+        for (index, id) in must_visit.iter().enumerate() {
+            visit_bits[*id] |= 1 << index;
+        }
+        let mut avoid = vec![false; self.len()];
+        for id in must_avoid {
+            avoid[*id] = true;
+        }
 
-        not_all_out = check_all_out_part2(&path_data)
+        return Constraints {
+            visit_bits,
+            avoid,
+            all_visited: (1 << must_visit.len()) - 1,
+        };
     }
 
-    dbg!(&path_data);
+    // Paths from the start to each device, by the must visit devices seen
+    // on the way, including at both ends. Each device passes its counts on
+    // to its outputs in order.
+    fn paths_from(
+        &self,
+        order: &[usize],
+        from: usize,
+        to: usize,
+        constraints: &Constraints,
+    ) -> Vec<Vec<u128>> {
+        let Constraints {
+            visit_bits,
+            avoid,
+            all_visited,
+        } = constraints;
+
+        let mut paths = vec![vec![0u128; all_visited + 1]; self.len()];
+        if !avoid[from] {
+            paths[from][visit_bits[from]] = 1;
+        }
+
+        for id in order {
+            // Paths stop once they get to the end
+            if *id == to {
+                continue;
+            }
+            for visited in 0..=*all_visited {
+                let count = paths[*id][visited];
+                if count == 0 {
+                    continue;
+                }
+                for output in &self.outputs[*id] {
+                    if !avoid[*output] {
+                        paths[*output][visited | visit_bits[*output]] += count;
+                    }
+                }
+            }
+        }
 
-    let out_node_index = find_node(&path_data, "out", true, true).expect("Expected a NodeData here");
+        return paths;
+    }
 
-    let out_node = &path_data[out_node_index];
+    // Paths from each device to the end, by the must visit devices seen
+    // after leaving it. Each device collects its outputs' counts, going
+    // backwards through the order.
+    fn paths_to(&self, order: &[usize], to: usize, constraints: &Constraints) -> Vec<Vec<u128>> {
+        let Constraints {
+            visit_bits,
+            avoid,
+            all_visited,
+        } = constraints;
 
-    let answer = out_node.count as u64;
-    return Some(Answer { answer });
-}
+        let mut paths = vec![vec![0u128; all_visited + 1]; self.len()];
+        if !avoid[to] {
+            paths[to][0] = 1;
+        }
 
-fn find_node(path_data: &Vec<NodeData>, node_name: &str, dac: bool, fft: bool) -> Option<usize> {
-    for (i, node_data) in path_data.iter().enumerate() {
-        if node_data.node_name == node_name && node_data.dac == dac && node_data.fft == fft {
-            return Some(i);
+        for id in order.iter().rev() {
+            if *id == to || avoid[*id] {
+                continue;
+            }
+            for output in &self.outputs[*id] {
+                if avoid[*output] {
+                    continue;
+                }
+                for visited in 0..=*all_visited {
+                    let count = paths[*output][visited];
+                    if count != 0 {
+                        paths[*id][visited | visit_bits[*output]] += count;
+                    }
+                }
+            }
         }
+
+        return paths;
+    }
+
+    // Number of different paths from one device to another that go through
+    // every must visit device and none of the must avoid ones
+    fn count_paths(
+        &self,
+        from: usize,
+        to: usize,
+        must_visit: &[usize],
+        must_avoid: &[usize],
+    ) -> u128 {
+        let order = self
+            .topological_order()
+            .expect("Expected the devices to not have a loop");
+        let constraints = self.constraints(must_visit, must_avoid);
+
+        let paths = self.paths_from(&order, from, to, &constraints);
+        return paths[to][constraints.all_visited];
+    }
+
+    // The same paths as count_paths, counted again for every device they go
+    // through. A device splits each path in two, and the must visit devices
+    // before and after it must make up the whole set between them.
+    fn paths_through(
+        &self,
+        from: usize,
+        to: usize,
+        must_visit: &[usize],
+        must_avoid: &[usize],
+    ) -> Vec<u128> {
+        let order = self
+            .topological_order()
+            .expect("Expected the devices to not have a loop");
+        let constraints = self.constraints(must_visit, must_avoid);
+        let all_visited = constraints.all_visited;
+
+        let before = self.paths_from(&order, from, to, &constraints);
+        let after = self.paths_to(&order, to, &constraints);
+
+        return (0..self.len())
+            .map(|id| {
+                (0..=all_visited)
+                    .map(|visited| before[id][visited] * after[id][all_visited ^ visited])
+                    .sum()
+            })
+            .collect();
     }
+}
+
+// Which must visit devices each device ticks off, as a bitmask, and which
+// devices paths can't go through
+struct Constraints {
+    visit_bits: Vec<usize>,
+    avoid: Vec<bool>,
+    all_visited: usize,
+}
+
+fn device_id(graph: &Graph, name: &str) -> usize {
+    return graph
+        .id(name)
+        .unwrap_or_else(|| panic!("Expected a device called {name}"));
+}
+
+fn count_paths(
+    graph: &Graph,
+    from: &str,
+    to: &str,
+    must_visit: &[&str],
+    must_avoid: &[&str],
+) -> u128 {
+    let must_visit: Vec<usize> = must_visit.iter().map(|name| device_id(graph, name)).collect();
+    let must_avoid: Vec<usize> = must_avoid.iter().map(|name| device_id(graph, name)).collect();
+    let (from, to) = (device_id(graph, from), device_id(graph, to));
+    return graph.count_paths(from, to, &must_visit, &must_avoid);
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum PathOrder {
+    Lexicographic,
+    ShortestFirst,
+}
 
-    return None
+// Lazily goes through the paths from start to end, as the device names on
+// each path. Only the current path is kept, so even graphs with far too many
+// paths to hold can be sampled. Lengths count the devices, including the
+// start and end.
+struct Paths<'a> {
+    graph: &'a Graph,
+    start: usize,
+    end: usize,
+    order: PathOrder,
+    max_count: Option<usize>,
+    max_length: Option<usize>,
+    // Every number of devices on a path from each device to the end, as a
+    // bitset with bit n set if there's a path of exactly n devices
+    lengths: Vec<Vec<u64>>,
+    sorted_outputs: Vec<Vec<usize>>,
+    // Each device on the current path, with the index of the next output to try
+    path: Vec<(usize, usize)>,
+    // Shortest first looks for paths of exactly this length, one length at a
+    // time. None before the first search has started.
+    length: Option<usize>,
+    found: usize,
 }
 
-fn check_all_out_part2(path_data: &Vec<NodeData>) -> bool {
-    for node_data in path_data {
-        if node_data.node_name != "out" && node_data.count != 0 {
-            // One is not all out
-            return true;
+impl Graph {
+    fn paths<'a>(&'a self, start: &str, end: &str) -> Paths<'a> {
+        let start = device_id(self, start);
+        let end = device_id(self, end);
+        let order = self
+            .topological_order()
+            .expect("Expected the devices to not have a loop");
+
+        // A path can't be longer than the number of devices, so this many
+        // words holds every length
+        let words = self.len() / 64 + 1;
+        let mut lengths = vec![vec![0u64; words]; self.len()];
+        for id in order.into_iter().rev() {
+            if id == end {
+                lengths[id][0] = 1 << 1;
+                continue;
+            }
+            let mut from_outputs = vec![0u64; words];
+            for output in &self.outputs[id] {
+                for (word, bits) in from_outputs.iter_mut().zip(&lengths[*output]) {
+                    *word |= bits;
+                }
+            }
+            // One more device than the paths from the outputs
+            let mut carry = 0;
+            for word in from_outputs.iter_mut() {
+                let next_carry = *word >> 63;
+                *word = (*word << 1) | carry;
+                carry = next_carry;
+            }
+            lengths[id] = from_outputs;
         }
+
+        // Repeated outputs are separate wires, so they're kept to give the
+        // same paths that count_paths counts
+        let sorted_outputs = self
+            .outputs
+            .iter()
+            .map(|outputs| {
+                let mut outputs = outputs.clone();
+                outputs.sort_by_key(|output| self.name(*output));
+                outputs
+            })
+            .collect();
+
+        return Paths {
+            graph: self,
+            start,
+            end,
+            order: PathOrder::Lexicographic,
+            max_count: None,
+            max_length: None,
+            lengths,
+            sorted_outputs,
+            path: vec![],
+            length: None,
+            found: 0,
+        };
     }
-    // All are out
-    return false;
 }
 
-fn find_next_nodes_part2(
-    node_map: HashMap<String, Node>,
-    path_data: Vec<NodeData>,
-) -> Vec<NodeData> {
-    let mut new_locations = vec![];
-
-    for old_node_data in &path_data {
-        let node_name = &old_node_data.node_name;
-        println!("Node name is {node_name}");
-        let out_names= vec!["out".to_string()];
-        let node_new_locations;
-        if node_name == "out" {
-            node_new_locations = &out_names;
-        } else {
-            node_new_locations = &node_map[node_name].outputs;
-        }
-        // Add the out location count, and status of dac and fft to the new locations
-        // If they ARE dac or fft then update them to show fft and/ or dac were passed
-
-        let mut dac = old_node_data.dac;
-        let mut fft = old_node_data.fft;
-
-        for new_location_name in node_new_locations {
-            if new_location_name == "dac" {
-                dac = true;
+impl<'a> Paths<'a> {
+    fn with_order(mut self, order: PathOrder) -> Self {
+        self.order = order;
+        return self;
+    }
+
+    fn with_max_count(mut self, max_count: usize) -> Self {
+        self.max_count = Some(max_count);
+        return self;
+    }
+
+    fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        return self;
+    }
+
+    // Whether a path with this many devices so far, ending at id, can still
+    // be finished within the lengths being looked for
+    fn can_finish(&self, length_so_far: usize, id: usize) -> bool {
+        let Some(shortest) = self.next_length(id, 0) else {
+            return false;
+        };
+        let fewest = length_so_far + shortest - 1;
+
+        if self.max_length.is_some_and(|max_length| fewest > max_length) {
+            return false;
+        }
+        return match (self.order, self.length) {
+            (PathOrder::ShortestFirst, Some(length)) => {
+                // The rest of the path has to be exactly the right length
+                length >= length_so_far
+                    && self.next_length(id, length - length_so_far + 1)
+                        == Some(length - length_so_far + 1)
             }
-            if new_location_name == "fft" {
-                fft = true;
+            _ => true,
+        };
+    }
+
+    // The fewest devices, at least min, on a path from id to the end
+    fn next_length(&self, id: usize, min: usize) -> Option<usize> {
+        let lengths = &self.lengths[id];
+        let mut index = min / 64;
+        let mut word = lengths.get(index)? & (u64::MAX << (min % 64));
+        loop {
+            if word != 0 {
+                return Some(index * 64 + word.trailing_zeros() as usize);
             }
-            let existing_new_index = find_node(&new_locations, new_location_name, dac, fft);
-            match existing_new_index {
-                None => new_locations.push(NodeData {
-                    node_name: new_location_name.to_string(),
-                    dac,
-                    fft,
-                    count: old_node_data.count,
-                }),
-                Some(index) => {
-                    let node = new_locations.get_mut(index).expect("Expected a node here");
-                    node.count += old_node_data.count;
+            index += 1;
+            word = *lengths.get(index)?;
+        }
+    }
+
+    // Starts the next search from the start, returning false once there are
+    // none left
+    fn start_search(&mut self) -> bool {
+        let next_length = match (self.order, self.length) {
+            (PathOrder::Lexicographic, None) => 0,
+            (PathOrder::Lexicographic, Some(_)) => return false,
+            // Only lengths there are paths of get searched
+            (PathOrder::ShortestFirst, previous) => {
+                let min = previous.map_or(0, |length| length + 1);
+                let Some(length) = self.next_length(self.start, min) else {
+                    return false;
+                };
+                if self.max_length.is_some_and(|max_length| length > max_length) {
+                    return false;
                 }
+                length
             }
+        };
+        self.length = Some(next_length);
+
+        if self.can_finish(1, self.start) {
+            self.path.push((self.start, 0));
         }
+        return true;
     }
+}
+
+impl<'a> Iterator for Paths<'a> {
+    type Item = Vec<&'a str>;
 
-    return new_locations;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.max_count.is_some_and(|max_count| self.found >= max_count) {
+            return None;
+        }
+
+        loop {
+            let Some((id, next)) = self.path.last().copied() else {
+                if !self.start_search() {
+                    return None;
+                }
+                continue;
+            };
+
+            // Paths stop once they get to the end
+            if id == self.end {
+                let graph = self.graph;
+                let names = self.path.iter().map(|(on, _)| graph.name(*on)).collect();
+                self.path.pop();
+                self.found += 1;
+                return Some(names);
+            }
+
+            let Some(output) = self.sorted_outputs[id].get(next).copied() else {
+                self.path.pop();
+                continue;
+            };
+            if let Some(last) = self.path.last_mut() {
+                last.1 += 1;
+            }
+            if self.can_finish(self.path.len() + 1, output) {
+                self.path.push((output, 0));
+            }
+        }
+    }
+}
+
+const START_COLOUR: &str = "#3cb44b";
+const END_COLOUR: &str = "#e6194b";
+const WAYPOINT_COLOUR: &str = "#4363d8";
+const ON_PATH_COLOUR: &str = "#ffe119";
+const OFF_PATH_COLOUR: &str = "#a9a9a9";
+
+// The devices ready to be written out for external tools, with the number of
+// paths from start to end through each device
+struct GraphExport<'a> {
+    graph: &'a Graph,
+    start: usize,
+    end: usize,
+    waypoints: Vec<usize>,
+    paths_through: Vec<u128>,
+}
+
+impl Graph {
+    fn export<'a>(&'a self, start: &str, end: &str, waypoints: &[&str]) -> GraphExport<'a> {
+        let start = device_id(self, start);
+        let end = device_id(self, end);
+        let waypoints: Vec<usize> = waypoints.iter().map(|name| device_id(self, name)).collect();
+
+        let paths_through = self.paths_through(start, end, &waypoints, &[]);
+
+        return GraphExport {
+            graph: self,
+            start,
+            end,
+            waypoints,
+            paths_through,
+        };
+    }
+}
+
+// Device names go inside quoted labels, so quotes and backslashes in them
+// need escaping. Mermaid has no backslash escapes, it uses entity codes.
+fn escape_dot(name: &str) -> String {
+    return name.replace('\\', "\\\\").replace('"', "\\\"");
+}
+
+fn escape_mermaid(name: &str) -> String {
+    return name.replace('"', "#quot;");
+}
+
+impl GraphExport<'_> {
+    fn colour(&self, id: usize) -> &'static str {
+        if id == self.start {
+            return START_COLOUR;
+        }
+        if id == self.end {
+            return END_COLOUR;
+        }
+        if self.waypoints.contains(&id) {
+            return WAYPOINT_COLOUR;
+        }
+        if self.paths_through[id] > 0 {
+            return ON_PATH_COLOUR;
+        }
+        return OFF_PATH_COLOUR;
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = "digraph devices {\n    rankdir=LR;\n    node [style=filled];\n".to_string();
+
+        for id in 0..self.graph.len() {
+            out.push_str(&format!(
+                "    n{id} [label=\"{}\\n{}\", fillcolor=\"{}\"];\n",
+                escape_dot(self.graph.name(id)),
+                self.paths_through[id],
+                self.colour(id)
+            ));
+        }
+
+        for (id, outputs) in self.graph.outputs.iter().enumerate() {
+            for output in outputs {
+                out.push_str(&format!("    n{id} -> n{output};\n"));
+            }
+        }
+
+        out.push_str("}\n");
+        return out;
+    }
+
+    fn to_mermaid(&self) -> String {
+        let mut out = "flowchart LR\n".to_string();
+
+        for id in 0..self.graph.len() {
+            out.push_str(&format!(
+                "    n{id}[\"{}<br/>{}\"]\n",
+                escape_mermaid(self.graph.name(id)),
+                self.paths_through[id]
+            ));
+        }
+
+        for (id, outputs) in self.graph.outputs.iter().enumerate() {
+            for output in outputs {
+                out.push_str(&format!("    n{id} --> n{output}\n"));
+            }
+        }
+
+        for id in 0..self.graph.len() {
+            out.push_str(&format!("    style n{id} fill:{}\n", self.colour(id)));
+        }
+        return out;
+    }
+}
+
+fn write_to_file(path: &str, text: &str) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+// Parses the devices, panicking with every problem that would stop paths
+// from start to end being counted
+fn parse_checked_graph(contents: &String, start: &str, end: &str) -> Graph {
+    let graph = Graph::new(&parse_input(contents));
+
+    if let Err(errors) = graph.validate(start, end) {
+        let fatal: Vec<String> = errors
+            .iter()
+            .filter(|error| error.is_fatal())
+            .map(|error| error.to_string())
+            .collect();
+        if !fatal.is_empty() {
+            panic!("Expected the devices to be valid, but {}", fatal.join(", "));
+        }
+    }
+
+    return graph;
+}
+
+fn part1(contents: &String) -> Option<Answer> {
+    let graph = parse_checked_graph(contents, "you", "out");
+
+    let paths = count_paths(&graph, "you", "out", &[], &[]);
+
+    let answer = u64::try_from(paths).expect("Expected the answer to fit in a u64");
+    return Some(Answer { answer });
+}
+
+// Part 1 attempted answers
+// 662: Correct!
+
+fn part2(contents: &String) -> Option<Answer> {
+    let graph = parse_checked_graph(contents, "svr", "out");
+
+    let paths = count_paths(&graph, "svr", "out", &["dac", "fft"], &[]);
+
+    let answer = u64::try_from(paths).expect("Expected the answer to fit in a u64");
+    return Some(Answer { answer });
 }
 
 // Part 2 attempted answers
@@ -244,6 +820,8 @@ fn main() {
     let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
     let do_part1 = false;
     let do_part2 = true;
+    let do_export = false;
+    let show_paths = false;
     if do_part1 {
         let result1 = part1(&contents);
         println!("Part1 result {result1:?}");
@@ -253,6 +831,28 @@ fn main() {
         let result2 = part2(&contents);
         println!("Part2 result {result2:?}");
     }
+
+    if do_export {
+        let graph = parse_checked_graph(&contents, "svr", "out");
+        let export = graph.export("svr", "out", &["dac", "fft"]);
+        write_to_file("devices.dot", &export.to_dot()).expect("Expected to write the DOT file");
+        write_to_file("devices.mmd", &export.to_mermaid())
+            .expect("Expected to write the Mermaid file");
+    }
+
+    if show_paths {
+        let graph = parse_checked_graph(&contents, "you", "out");
+        let shortest = graph
+            .paths("you", "out")
+            .with_order(PathOrder::ShortestFirst)
+            .with_max_count(10);
+        for path in shortest {
+            println!("{}", path.join(" -> "));
+        }
+
+        let short = graph.paths("you", "out").with_max_length(8).count();
+        println!("There are {short} paths through at most 8 devices");
+    }
 }
 
 // Tests
@@ -297,6 +897,20 @@ hhh: out"
         }
     }
 
+    // Every layer has two devices that both output to both devices in the
+    // next layer, so the paths double each time
+    fn layered_graph(layers: usize) -> Graph {
+        let mut lines = vec!["start: a0 b0".to_string()];
+        for layer in 0..layers - 1 {
+            let next = format!("a{} b{}", layer + 1, layer + 1);
+            lines.push(format!("a{layer}: {next}"));
+            lines.push(format!("b{layer}: {next}"));
+        }
+        lines.push(format!("a{}: out", layers - 1));
+        lines.push(format!("b{}: out", layers - 1));
+        return Graph::new(&parse_input(&lines.join("\n")));
+    }
+
     #[test]
     fn test_part1_example() {
         let setup = Setup::new();
@@ -320,11 +934,361 @@ hhh: out"
         assert_eq!(result, Some(Answer { answer: 2 }));
     }
 
-    #[ignore]
     #[test]
     fn test_part2() {
         let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
         let result = part2(&contents);
-        assert_eq!(result, Some(Answer { answer: 2341 }));
+        assert_eq!(
+            result,
+            Some(Answer {
+                answer: 429399933071120
+            })
+        );
+    }
+
+    #[test]
+    fn test_graph_interning() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents));
+
+        // 10 devices with lines, plus out
+        assert_eq!(graph.len(), 11);
+        assert_eq!(graph.id("aaa"), Some(0));
+        assert_eq!(graph.id("you"), Some(1));
+        assert_eq!(graph.id("hhh"), Some(2));
+        assert_eq!(graph.id("missing"), None);
+
+        let out = graph.id("out").expect("Expected out");
+        assert!(graph.outputs[out].is_empty());
+        let ccc = graph.id("ccc").expect("Expected ccc");
+        assert_eq!(graph.outputs[ccc].len(), 3);
+    }
+
+    #[test]
+    fn test_count_paths() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents2));
+
+        assert_eq!(count_paths(&graph, "svr", "out", &[], &[]), 8);
+        assert_eq!(count_paths(&graph, "svr", "fft", &[], &[]), 1);
+        assert_eq!(count_paths(&graph, "fft", "dac", &[], &[]), 1);
+        assert_eq!(count_paths(&graph, "dac", "out", &[], &[]), 2);
+        assert_eq!(count_paths(&graph, "dac", "fft", &[], &[]), 0);
+        assert_eq!(count_paths(&graph, "out", "out", &[], &[]), 1);
+    }
+
+    #[test]
+    fn test_count_paths_many_layers() {
+        let layers = 100;
+        let graph = layered_graph(layers);
+        assert_eq!(count_paths(&graph, "start", "out", &[], &[]), 1u128 << layers);
+    }
+
+    #[test]
+    fn test_validate_examples() {
+        let setup = Setup::new();
+
+        let graph = Graph::new(&parse_input(&setup.contents));
+        assert_eq!(
+            graph.validate("you", "out"),
+            Err(vec![GraphError::Unreachable {
+                start: "you".to_string(),
+                devices: vec!["aaa".to_string(), "hhh".to_string(), "iii".to_string()],
+            }])
+        );
+
+        let graph = Graph::new(&parse_input(&setup.contents2));
+        assert_eq!(graph.validate("svr", "out"), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_cycle() {
+        let contents = "you: aaa
+aaa: bbb out
+bbb: ccc
+ccc: aaa"
+            .to_string();
+        let graph = Graph::new(&parse_input(&contents));
+
+        let errors = graph.validate("you", "out").expect_err("Expected a loop");
+        assert_eq!(
+            errors,
+            vec![GraphError::Cycle {
+                devices: vec![
+                    "aaa".to_string(),
+                    "bbb".to_string(),
+                    "ccc".to_string(),
+                    "aaa".to_string()
+                ],
+            }]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "the devices loop round aaa -> bbb -> ccc -> aaa"
+        );
+    }
+
+    #[test]
+    fn test_validate_undefined_and_missing() {
+        let contents = "you: aaa bbb
+aaa: out
+bbb: ccc"
+            .to_string();
+        let graph = Graph::new(&parse_input(&contents));
+
+        assert_eq!(
+            graph.validate("svr", "out"),
+            Err(vec![
+                GraphError::MissingDevice {
+                    device: "svr".to_string()
+                },
+                GraphError::UndefinedOutput {
+                    device: "bbb".to_string(),
+                    output: "ccc".to_string()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the devices loop round aaa -> aaa")]
+    fn test_part1_cycle() {
+        part1(&"you: aaa\naaa: aaa out".to_string());
+    }
+
+    #[test]
+    fn test_count_paths_waypoints() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents2));
+
+        assert_eq!(count_paths(&graph, "svr", "out", &["dac"], &[]), 4);
+        assert_eq!(count_paths(&graph, "svr", "out", &["fft", "dac"], &[]), 2);
+        assert_eq!(count_paths(&graph, "svr", "out", &["dac", "fft", "hhh"], &[]), 1);
+        assert_eq!(count_paths(&graph, "svr", "out", &["dac", "hub"], &[]), 0);
+        assert_eq!(count_paths(&graph, "svr", "out", &["svr", "out"], &[]), 8);
+
+        assert_eq!(count_paths(&graph, "svr", "out", &[], &["ggg"]), 4);
+        assert_eq!(count_paths(&graph, "svr", "out", &["dac"], &["fft"]), 2);
+        assert_eq!(count_paths(&graph, "svr", "out", &["dac"], &["dac"]), 0);
+        assert_eq!(count_paths(&graph, "svr", "out", &[], &["svr"]), 0);
+    }
+
+    #[test]
+    fn test_export_paths_through() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents2));
+        let export = graph.export("svr", "out", &["dac", "fft"]);
+
+        let through = |name: &str| export.paths_through[device_id(&graph, name)];
+        assert_eq!(through("svr"), 2);
+        assert_eq!(through("out"), 2);
+        assert_eq!(through("aaa"), 2);
+        assert_eq!(through("ggg"), 1);
+        assert_eq!(through("bbb"), 0);
+        assert_eq!(through("hub"), 0);
+    }
+
+    #[test]
+    fn test_paths_through_matches_count() {
+        let setup = Setup::new();
+        for (contents, start, waypoints) in [
+            (&setup.contents, "you", vec![]),
+            (&setup.contents2, "svr", vec!["dac", "fft"]),
+        ] {
+            let graph = Graph::new(&parse_input(contents));
+            let start = device_id(&graph, start);
+            let end = device_id(&graph, "out");
+            let waypoints: Vec<usize> = waypoints
+                .iter()
+                .map(|name| device_id(&graph, name))
+                .collect();
+
+            let through = graph.paths_through(start, end, &waypoints, &[]);
+            for (id, count) in through.iter().enumerate() {
+                let mut must_visit = waypoints.clone();
+                must_visit.push(id);
+                assert_eq!(*count, graph.count_paths(start, end, &must_visit, &[]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_dot() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents2));
+        let dot = graph.export("svr", "out", &["dac", "fft"]).to_dot();
+
+        assert!(dot.starts_with("digraph devices {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("    n0 [label=\"svr\\n2\", fillcolor=\"#3cb44b\"];\n"));
+        assert!(dot.contains("    n3 [label=\"fft\\n2\", fillcolor=\"#4363d8\"];\n"));
+        assert!(dot.contains("    n2 [label=\"bbb\\n0\", fillcolor=\"#a9a9a9\"];\n"));
+        assert!(dot.contains("    n0 -> n1;\n"));
+        assert_eq!(dot.matches(" -> ").count(), 16);
+    }
+
+    #[test]
+    fn test_export_mermaid() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents));
+        let mermaid = graph.export("you", "out", &[]).to_mermaid();
+
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("    n1[\"you<br/>5\"]\n"));
+        assert!(mermaid.contains("    n1 --> n3\n"));
+        assert!(mermaid.contains("    style n1 fill:#3cb44b\n"));
+        assert!(mermaid.contains("    style n0 fill:#a9a9a9\n"));
+    }
+
+    #[test]
+    fn test_export_escapes_names() {
+        let contents = "a\"b: c\\d\nc\\d: out".to_string();
+        let graph = Graph::new(&parse_input(&contents));
+        let export = graph.export("a\"b", "out", &[]);
+
+        let dot = export.to_dot();
+        assert!(dot.contains("    n0 [label=\"a\\\"b\\n1\""));
+        assert!(dot.contains("    n1 [label=\"c\\\\d\\n1\""));
+
+        let mermaid = export.to_mermaid();
+        assert!(mermaid.contains("    n0[\"a#quot;b<br/>1\"]\n"));
+        assert!(mermaid.contains("    n1[\"c\\d<br/>1\"]\n"));
+    }
+
+    #[test]
+    fn test_paths_lexicographic() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents));
+
+        let paths: Vec<Vec<&str>> = graph.paths("you", "out").collect();
+        assert_eq!(
+            paths,
+            vec![
+                vec!["you", "bbb", "ddd", "ggg", "out"],
+                vec!["you", "bbb", "eee", "out"],
+                vec!["you", "ccc", "ddd", "ggg", "out"],
+                vec!["you", "ccc", "eee", "out"],
+                vec!["you", "ccc", "fff", "out"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paths_shortest_first() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents));
+
+        let paths: Vec<Vec<&str>> = graph
+            .paths("you", "out")
+            .with_order(PathOrder::ShortestFirst)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                vec!["you", "bbb", "eee", "out"],
+                vec!["you", "ccc", "eee", "out"],
+                vec!["you", "ccc", "fff", "out"],
+                vec!["you", "bbb", "ddd", "ggg", "out"],
+                vec!["you", "ccc", "ddd", "ggg", "out"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paths_limits() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents));
+
+        assert_eq!(graph.paths("you", "out").with_max_count(2).count(), 2);
+        assert_eq!(graph.paths("you", "out").with_max_length(4).count(), 3);
+        assert_eq!(graph.paths("you", "out").with_max_length(3).count(), 0);
+        assert_eq!(
+            graph
+                .paths("you", "out")
+                .with_order(PathOrder::ShortestFirst)
+                .with_max_length(4)
+                .with_max_count(10)
+                .count(),
+            3
+        );
+        assert_eq!(graph.paths("ddd", "eee").count(), 0);
+        assert_eq!(graph.paths("out", "out").collect::<Vec<_>>(), vec![vec!["out"]]);
+    }
+
+    #[test]
+    fn test_paths_length_gaps() {
+        // Paths of 3 and 5 devices, but none of 4 even though that's between
+        // the shortest and longest
+        let contents = "you: bbb\nbbb: out ccc\nccc: ddd\nddd: out".to_string();
+        let graph = Graph::new(&parse_input(&contents));
+
+        let mut paths = graph.paths("you", "out").with_order(PathOrder::ShortestFirst);
+        assert_eq!(paths.next_length(paths.start, 0), Some(3));
+        assert_eq!(paths.next_length(paths.start, 4), Some(5));
+        assert_eq!(paths.next_length(paths.start, 6), None);
+        paths.length = Some(4);
+        assert!(!paths.can_finish(1, paths.start));
+        paths.length = Some(5);
+        assert!(paths.can_finish(1, paths.start));
+
+        let lengths: Vec<usize> = graph
+            .paths("you", "out")
+            .with_order(PathOrder::ShortestFirst)
+            .map(|path| path.len())
+            .collect();
+        assert_eq!(lengths, vec![3, 5]);
+        assert_eq!(
+            graph
+                .paths("you", "out")
+                .with_order(PathOrder::ShortestFirst)
+                .with_max_length(4)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_paths_match_count() {
+        let setup = Setup::new();
+        let graph = Graph::new(&parse_input(&setup.contents2));
+
+        for order in [PathOrder::Lexicographic, PathOrder::ShortestFirst] {
+            let paths: Vec<Vec<&str>> = graph.paths("svr", "out").with_order(order).collect();
+            assert_eq!(paths.len() as u128, count_paths(&graph, "svr", "out", &[], &[]));
+            let through_both = paths
+                .iter()
+                .filter(|path| path.contains(&"dac") && path.contains(&"fft"))
+                .count();
+            assert_eq!(through_both, 2);
+        }
+    }
+
+    #[test]
+    fn test_paths_repeated_output() {
+        let contents = "you: aaa aaa bbb\naaa: out\nbbb: out out".to_string();
+        let graph = Graph::new(&parse_input(&contents));
+
+        for order in [PathOrder::Lexicographic, PathOrder::ShortestFirst] {
+            let paths: Vec<Vec<&str>> = graph.paths("you", "out").with_order(order).collect();
+            assert_eq!(paths.len() as u128, count_paths(&graph, "you", "out", &[], &[]));
+            assert_eq!(paths.len(), 4);
+        }
+    }
+
+    #[test]
+    fn test_paths_many_layers() {
+        // 2^100 paths, so this only works if they're found lazily
+        let layers = 100;
+        let graph = layered_graph(layers);
+
+        let paths: Vec<Vec<&str>> = graph
+            .paths("start", "out")
+            .with_order(PathOrder::ShortestFirst)
+            .with_max_count(3)
+            .collect();
+        assert_eq!(paths.len(), 3);
+        assert!(paths.iter().all(|path| path.len() == layers + 2));
+        assert_eq!(paths[0][1..4], ["a0", "a1", "a2"]);
+        assert_eq!(paths[2][layers - 1..], ["b98", "a99", "out"]);
     }
 }
@@ -1,5 +1,4 @@
-use eval::eval;
-use std::fs;
+use std::{fmt, fs};
 
 #[derive(Debug, PartialEq)]
 struct Answer {
@@ -20,145 +19,710 @@ impl InputGetter for LocalFileInputGetter {
     }
 }
 
-fn parse_data_part1(contents: &String) -> Vec<Vec<&str>> {
-    let line_data: Vec<Vec<&str>> = contents
-        .lines()
-        .map(|line| line.split(" ").filter(|x| *x != "").collect())
-        .collect();
-    return line_data;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
 }
 
-fn do_expressions(expressions: Vec<String>) -> u64 {
-    let mut answer = 0.0;
+impl Operator {
+    fn from_char(c: char) -> Option<Operator> {
+        return match c {
+            '+' => Some(Operator::Add),
+            '-' => Some(Operator::Subtract),
+            '*' => Some(Operator::Multiply),
+            '/' => Some(Operator::Divide),
+            _ => None,
+        };
+    }
 
-    dbg!(&expressions);
+    fn precedence(&self) -> u8 {
+        return match self {
+            Operator::Add | Operator::Subtract => 1,
+            Operator::Multiply | Operator::Divide => 2,
+        };
+    }
 
-    for expression in expressions {
-        let string_expression = expression.to_string();
-        if &string_expression == "" {
-            continue;
+    fn apply(&self, lhs: i128, rhs: i128) -> Result<i128, ExpressionError> {
+        return match self {
+            Operator::Add => lhs.checked_add(rhs).ok_or(ExpressionError::Overflow),
+            Operator::Subtract => lhs.checked_sub(rhs).ok_or(ExpressionError::Overflow),
+            Operator::Multiply => lhs.checked_mul(rhs).ok_or(ExpressionError::Overflow),
+            Operator::Divide => {
+                if rhs == 0 {
+                    return Err(ExpressionError::DivisionByZero);
+                }
+                // Keep everything exact, a remainder would have to be rounded away
+                if lhs % rhs != 0 {
+                    return Err(ExpressionError::InexactDivision { lhs, rhs });
+                }
+                lhs.checked_div(rhs).ok_or(ExpressionError::Overflow)
+            }
+        };
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+        };
+        write!(f, "{c}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExpressionError {
+    UnexpectedCharacter { character: char, position: usize },
+    UnexpectedEnd,
+    UnmatchedParenthesis { position: usize },
+    EmptyExpression,
+    Overflow,
+    DivisionByZero,
+    InexactDivision { lhs: i128, rhs: i128 },
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpressionError::UnexpectedCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "unexpected character '{character}' at position {position}"
+            ),
+            ExpressionError::UnexpectedEnd => write!(f, "expression ended unexpectedly"),
+            ExpressionError::UnmatchedParenthesis { position } => {
+                write!(f, "unmatched parenthesis at position {position}")
+            }
+            ExpressionError::EmptyExpression => write!(f, "expression has no values"),
+            ExpressionError::Overflow => write!(f, "arithmetic overflow"),
+            ExpressionError::DivisionByZero => write!(f, "division by zero"),
+            ExpressionError::InexactDivision { lhs, rhs } => {
+                write!(f, "{lhs} is not exactly divisible by {rhs}")
+            }
         }
-        dbg!(&string_expression);
-        let eval_expression = &string_expression[..string_expression.len() - 1];
-        dbg!(eval_expression);
-        let out = eval(eval_expression)
-            .expect("Expected a value here")
-            .as_f64()
-            .expect("Expected a float here");
-        dbg!(out);
-        answer += out;
     }
+}
 
-    dbg!(answer);
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expression {
+    Number(u64),
+    Group(Box<Expression>),
+    Binary {
+        operator: Operator,
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+}
+
+impl Expression {
+    // Builds the expression for a single worksheet problem, which is every
+    // value joined by the same operator, e.g. 123 * 45 * 6
+    fn chain(operator: Operator, values: &[u64]) -> Result<Expression, ExpressionError> {
+        let (first, rest) = values
+            .split_first()
+            .ok_or(ExpressionError::EmptyExpression)?;
 
-    let answer_64 = answer.round() as u64;
+        let mut expression = Expression::Number(*first);
+        for value in rest {
+            expression = Expression::Binary {
+                operator,
+                lhs: Box::new(expression),
+                rhs: Box::new(Expression::Number(*value)),
+            };
+        }
+        return Ok(expression);
+    }
 
-    return answer_64;
+    fn evaluate(&self) -> Result<i128, ExpressionError> {
+        return match self {
+            Expression::Number(value) => Ok(*value as i128),
+            Expression::Group(inner) => inner.evaluate(),
+            Expression::Binary { operator, lhs, rhs } => {
+                operator.apply(lhs.evaluate()?, rhs.evaluate()?)
+            }
+        };
+    }
 }
 
-fn part1(contents: &String) -> Option<Answer> {
-    let data = parse_data_part1(contents);
-    let first_line = data.get(0).expect("Expected a line here");
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{value}"),
+            Expression::Group(inner) => write!(f, "({inner})"),
+            Expression::Binary { operator, lhs, rhs } => {
+                write_operand(f, lhs, *operator, false)?;
+                write!(f, "{operator}")?;
+                write_operand(f, rhs, *operator, true)
+            }
+        }
+    }
+}
+
+// Brackets are only needed where the text would otherwise parse differently,
+// which is a looser operator on either side, or an equal one on the right as
+// everything is left associative
+fn write_operand(
+    f: &mut fmt::Formatter<'_>,
+    operand: &Expression,
+    parent: Operator,
+    is_rhs: bool,
+) -> fmt::Result {
+    let needs_brackets = match operand {
+        Expression::Binary { operator, .. } => {
+            operator.precedence() < parent.precedence()
+                || (is_rhs && operator.precedence() == parent.precedence())
+        }
+        _ => false,
+    };
+    if needs_brackets {
+        return write!(f, "({operand})");
+    }
+    return write!(f, "{operand}");
+}
 
-    let mut expressions: Vec<String> = vec!["".to_string(); first_line.len()];
+// Recursive descent parser, with * and / binding tighter than + and -
+struct ExpressionParser<'a> {
+    chars: Vec<char>,
+    position: usize,
+    text: &'a str,
+}
 
-    for (i, _) in first_line.iter().enumerate() {
-        for line in &data[..data.len() - 1] {
-            expressions[i] += line[i];
-            expressions[i] += data[data.len() - 1][i];
+impl<'a> ExpressionParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().collect(),
+            position: 0,
+            text,
         }
     }
 
-    dbg!(&expressions);
+    fn skip_whitespace(&mut self) {
+        while self.position < self.chars.len() && self.chars[self.position].is_whitespace() {
+            self.position += 1;
+        }
+    }
 
-    return Some(Answer {
-        answer: do_expressions(expressions),
-    });
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        return self.chars.get(self.position).copied();
+    }
+
+    fn parse(mut self) -> Result<Expression, ExpressionError> {
+        if self.text.trim().is_empty() {
+            return Err(ExpressionError::EmptyExpression);
+        }
+        let expression = self.parse_binary(1)?;
+        return match self.peek() {
+            None => Ok(expression),
+            Some(')') => Err(ExpressionError::UnmatchedParenthesis {
+                position: self.position,
+            }),
+            Some(character) => Err(ExpressionError::UnexpectedCharacter {
+                character,
+                position: self.position,
+            }),
+        };
+    }
+
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expression, ExpressionError> {
+        let mut lhs = self.parse_operand()?;
+
+        while let Some(operator) = self.peek().and_then(Operator::from_char) {
+            if operator.precedence() < min_precedence {
+                break;
+            }
+            self.position += 1;
+            // Left associative, so the right hand side only takes tighter operators
+            let rhs = self.parse_binary(operator.precedence() + 1)?;
+            lhs = Expression::Binary {
+                operator,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+
+        return Ok(lhs);
+    }
+
+    fn parse_operand(&mut self) -> Result<Expression, ExpressionError> {
+        match self.peek() {
+            None => Err(ExpressionError::UnexpectedEnd),
+            Some('(') => {
+                let open_position = self.position;
+                self.position += 1;
+                let inner = self.parse_binary(1)?;
+                if self.peek() != Some(')') {
+                    return Err(ExpressionError::UnmatchedParenthesis {
+                        position: open_position,
+                    });
+                }
+                self.position += 1;
+                Ok(Expression::Group(Box::new(inner)))
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut value: u64 = 0;
+                while let Some(digit) = self.chars.get(self.position).and_then(|c| c.to_digit(10)) {
+                    value = value
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(digit as u64))
+                        .ok_or(ExpressionError::Overflow)?;
+                    self.position += 1;
+                }
+                Ok(Expression::Number(value))
+            }
+            Some(character) => Err(ExpressionError::UnexpectedCharacter {
+                character,
+                position: self.position,
+            }),
+        }
+    }
 }
 
-// Part 1 attempted answers
-// 566612075051 too low
+fn parse_expression(text: &str) -> Result<Expression, ExpressionError> {
+    return ExpressionParser::new(text).parse();
+}
 
-fn parse_data_part2(contents: &String) -> (Vec<Vec<String>>, Vec<String>) {
-    // Part 1 parses the data into each
-    // part1_data = [["123","328","51","64",]
-    //              ,["45","64","387","23",]
-    //              ,["6","98","215","314",]
-    //              ,["*","+","*","+",],]
+fn do_expressions(expressions: Vec<Expression>) -> u64 {
+    let mut answer: i128 = 0;
 
-    println!("{contents}");
-    let string_lines = contents
-        .lines()
-        .map(|line| line.to_string())
-        .collect::<Vec<String>>();
+    for expression in expressions {
+        let out = expression
+            .evaluate()
+            .unwrap_or_else(|err| panic!("Could not evaluate {expression}: {err}"));
+        answer = answer
+            .checked_add(out)
+            .expect("Expected the total to fit in an i128");
+    }
 
-    let first_line = string_lines.get(0).expect("Expected the first line here");
+    dbg!(answer);
 
-    let mut values: Vec<String> = vec![];
+    return u64::try_from(answer).expect("Expected the answer to fit in a u64");
+}
 
-    for i in (0..first_line.len()).rev() {
-        let mut current_value = "".to_string();
+// The longest run of digits that always fits in a u64
+const MAX_DIGITS: usize = 19;
 
-        for line in &string_lines[..string_lines.len() - 1] {
-            current_value += &line
-                .chars()
-                .nth(i)
-                .expect("Expected a character here")
-                .to_string();
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WorksheetError {
+    Empty,
+    UnexpectedCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    MissingOperator {
+        start_column: usize,
+    },
+    MultipleOperators {
+        start_column: usize,
+    },
+    NumberTooLong {
+        start_column: usize,
+    },
+}
+
+impl fmt::Display for WorksheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorksheetError::Empty => write!(f, "worksheet has no lines"),
+            WorksheetError::UnexpectedCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "unexpected character '{character}' on line {line} column {column}"
+            ),
+            WorksheetError::MissingOperator { start_column } => {
+                write!(f, "problem at column {start_column} has no operator")
+            }
+            WorksheetError::MultipleOperators { start_column } => {
+                write!(
+                    f,
+                    "problem at column {start_column} has more than one operator"
+                )
+            }
+            WorksheetError::NumberTooLong { start_column } => write!(
+                f,
+                "problem at column {start_column} has a number longer than {MAX_DIGITS} digits"
+            ),
         }
-        values.push(current_value);
     }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProblemSpan {
+    // Columns start..end of the worksheet belong to this problem
+    start: usize,
+    end: usize,
+    operator: Operator,
+}
+
+// Keeps the exact character layout of the worksheet, so that the numbers can
+// be read either along the rows or down the columns
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Worksheet {
+    number_lines: Vec<Vec<char>>,
+    operator_line: Vec<char>,
+    spans: Vec<ProblemSpan>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WorksheetProblem<'a> {
+    worksheet: &'a Worksheet,
+    span: &'a ProblemSpan,
+}
 
-    let mut out_data: Vec<Vec<String>> = vec![];
+fn cell(line: &[char], column: usize) -> char {
+    // Lines can be ragged, anything past the end of a line is blank
+    return line.get(column).copied().unwrap_or(' ');
+}
 
-    let mut current_line: Vec<String> = vec![];
+fn digits_to_number(chars: impl Iterator<Item = char>) -> Option<u64> {
+    let mut number = None;
+    for c in chars.filter(|c| *c != ' ') {
+        let digit = c
+            .to_digit(10)
+            .expect("Expected only digits in a worksheet number") as u64;
+        number = Some(number.unwrap_or(0) * 10 + digit);
+    }
+    return number;
+}
 
-    dbg!(&values);
+impl Worksheet {
+    fn parse(contents: &str) -> Result<Worksheet, WorksheetError> {
+        let mut lines: Vec<Vec<char>> = contents
+            .lines()
+            .map(|line| line.trim_end_matches('\r').chars().collect())
+            .collect();
 
-    for value in values {
-        if value.chars().filter(|c| *c == ' ').count() == value.len() {
-            out_data.push(current_line);
-            current_line = vec![];
-        } else {
-            current_line.push(value.replace(" ", ""));
+        while lines
+            .last()
+            .is_some_and(|line| line.iter().all(|c| *c == ' '))
+        {
+            lines.pop();
         }
+
+        let operator_line = lines.pop().ok_or(WorksheetError::Empty)?;
+        let number_lines = lines;
+
+        for (line_number, line) in number_lines.iter().enumerate() {
+            for (column, character) in line.iter().enumerate() {
+                if *character != ' ' && !character.is_ascii_digit() {
+                    return Err(WorksheetError::UnexpectedCharacter {
+                        line: line_number,
+                        column,
+                        character: *character,
+                    });
+                }
+            }
+        }
+
+        for (column, character) in operator_line.iter().enumerate() {
+            if *character != ' ' && Operator::from_char(*character).is_none() {
+                return Err(WorksheetError::UnexpectedCharacter {
+                    line: number_lines.len(),
+                    column,
+                    character: *character,
+                });
+            }
+        }
+
+        let width = number_lines
+            .iter()
+            .chain([&operator_line])
+            .map(|line| line.len())
+            .max()
+            .unwrap_or(0);
+
+        let is_blank_column = |column: usize| {
+            number_lines
+                .iter()
+                .chain([&operator_line])
+                .all(|line| cell(line, column) == ' ')
+        };
+
+        let mut spans = vec![];
+        let mut column = 0;
+        while column < width {
+            if is_blank_column(column) {
+                column += 1;
+                continue;
+            }
+
+            let start = column;
+            while column < width && !is_blank_column(column) {
+                column += 1;
+            }
+            let end = column;
+
+            let operators: Vec<Operator> = (start..end)
+                .filter_map(|c| Operator::from_char(cell(&operator_line, c)))
+                .collect();
+            let operator = match operators[..] {
+                [operator] => operator,
+                [] => {
+                    return Err(WorksheetError::MissingOperator {
+                        start_column: start,
+                    });
+                }
+                _ => {
+                    return Err(WorksheetError::MultipleOperators {
+                        start_column: start,
+                    });
+                }
+            };
+
+            // Only a limit when reading along the rows, the length of a
+            // column is checked when the columns are read
+            if end - start > MAX_DIGITS {
+                return Err(WorksheetError::NumberTooLong {
+                    start_column: start,
+                });
+            }
+
+            spans.push(ProblemSpan {
+                start,
+                end,
+                operator,
+            });
+        }
+
+        return Ok(Worksheet {
+            number_lines,
+            operator_line,
+            spans,
+        });
+    }
+
+    fn row_problems(&self) -> Vec<Problem> {
+        return self.problems().map(WorksheetProblem::row_problem).collect();
+    }
+
+    fn column_problems(&self) -> Result<Vec<Problem>, WorksheetError> {
+        return self
+            .problems()
+            .map(WorksheetProblem::column_problem)
+            .collect();
+    }
+
+    fn problems(&self) -> impl Iterator<Item = WorksheetProblem<'_>> {
+        return self.spans.iter().map(|span| WorksheetProblem {
+            worksheet: self,
+            span,
+        });
+    }
+}
+
+impl<'a> WorksheetProblem<'a> {
+    fn operator(&self) -> Operator {
+        return self.span.operator;
+    }
+
+    // Each line is one number, read left to right
+    fn row_numbers(&self) -> impl Iterator<Item = u64> + 'a {
+        let span = *self.span;
+        return self.worksheet.number_lines.iter().filter_map(move |line| {
+            digits_to_number((span.start..span.end).map(|c| cell(line, c)))
+        });
+    }
+
+    // Each column is one number, read top to bottom, starting from the rightmost column
+    fn column_numbers(&self) -> impl Iterator<Item = u64> + 'a {
+        let span = *self.span;
+        let lines = &self.worksheet.number_lines;
+        return (span.start..span.end)
+            .rev()
+            .filter_map(move |c| digits_to_number(lines.iter().map(|line| cell(line, c))));
     }
-    out_data.push(current_line);
 
-    // Now we just need to get the signs!
+    fn row_problem(self) -> Problem {
+        return Problem {
+            operator: self.operator(),
+            numbers: self.row_numbers().collect(),
+        };
+    }
+
+    fn column_problem(self) -> Result<Problem, WorksheetError> {
+        let span = *self.span;
+        let lines = &self.worksheet.number_lines;
+        let too_long = (span.start..span.end)
+            .any(|c| lines.iter().filter(|line| cell(line, c) != ' ').count() > MAX_DIGITS);
+        if too_long {
+            return Err(WorksheetError::NumberTooLong {
+                start_column: span.start,
+            });
+        }
+
+        return Ok(Problem {
+            operator: self.operator(),
+            numbers: self.column_numbers().collect(),
+        });
+    }
+}
+
+fn parse_worksheet(contents: &str) -> Worksheet {
+    return Worksheet::parse(contents)
+        .unwrap_or_else(|err| panic!("Expected a valid worksheet: {err}"));
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Problem {
+    operator: Operator,
+    numbers: Vec<u64>,
+}
+
+impl Problem {
+    fn to_expression(&self) -> Expression {
+        return Expression::chain(self.operator, &self.numbers)
+            .expect("Expected every problem to have a value");
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    Left,
+    Right,
+}
 
-    let signs: Vec<String> = string_lines
-        .get(string_lines.len() - 1)
-        .expect("Expected the last line here")
-        .split(" ")
-        .filter(|x| *x != "")
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>()
-        .into_iter()
-        .rev()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RenderOptions {
+    // Number of blank columns between problems, at least one is always used
+    padding: usize,
+    alignment: Alignment,
+}
+
+// Lays the problems out the same way as the puzzle input, one number per line
+// and the operator under the first column of each problem
+fn render_worksheet(problems: &[Problem], options: &RenderOptions) -> String {
+    let separator = " ".repeat(options.padding.max(1));
+    let rows = problems
+        .iter()
+        .map(|problem| problem.numbers.len())
+        .max()
+        .unwrap_or(0);
+    let widths: Vec<usize> = problems
+        .iter()
+        .map(|problem| {
+            problem
+                .numbers
+                .iter()
+                .map(|number| number.to_string().len())
+                .max()
+                .unwrap_or(1)
+        })
         .collect();
 
-    dbg!(&out_data);
+    let mut lines: Vec<String> = vec![];
+
+    for row in 0..rows {
+        let cells: Vec<String> = problems
+            .iter()
+            .zip(&widths)
+            .map(|(problem, width)| match problem.numbers.get(row) {
+                None => " ".repeat(*width),
+                Some(number) => match options.alignment {
+                    Alignment::Left => format!("{number:<width$}"),
+                    Alignment::Right => format!("{number:>width$}"),
+                },
+            })
+            .collect();
+        lines.push(cells.join(&separator));
+    }
+
+    let operators: Vec<String> = problems
+        .iter()
+        .zip(&widths)
+        .map(|(problem, width)| format!("{:<width$}", problem.operator.to_string()))
+        .collect();
+    lines.push(operators.join(&separator));
 
-    // Needs to
-    return (out_data, signs);
+    return lines.join("\n");
 }
 
-fn part2(contents: &String) -> Option<Answer> {
-    let (data, signs) = parse_data_part2(contents);
+// Small xorshift generator, so that new worksheets can be made without any
+// extra dependencies, and the same seed always gives the same worksheet
+struct ProblemGenerator {
+    state: u64,
+}
 
-    // Expresions is a Vector where each item is a String that is a expression which is ready to parse
-    let mut expressions: Vec<String> = vec![];
+impl ProblemGenerator {
+    fn new(seed: u64) -> Self {
+        // xorshift gets stuck on zero
+        Self { state: seed.max(1) }
+    }
 
-    for (i, line) in data.iter().enumerate() {
-        let mut expression = "".to_string();
-        for value in line {
-            expression.push_str(&value);
-            expression.push_str(signs.get(i).expect("Expected a sign from this list"));
-        }
-        expressions.push(expression);
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return self.state;
+    }
+
+    fn next_in_range(&mut self, low: u64, high: u64) -> u64 {
+        return low + self.next_u64() % (high - low + 1);
+    }
+
+    fn next_problem(&mut self, max_numbers: usize, max_digits: u32) -> Problem {
+        // Only the puzzle's operators, as subtracting or dividing could make a
+        // problem that has no whole positive answer
+        let operators = [Operator::Add, Operator::Multiply];
+        let operator = operators[self.next_in_range(0, 1) as usize];
+        // Every problem needs at least one number with at least one digit
+        let count = self.next_in_range(1, max_numbers.max(1) as u64);
+        let numbers = (0..count)
+            .map(|_| {
+                let digits = self.next_in_range(1, max_digits.max(1) as u64) as u32;
+                self.next_in_range(0, 10u64.pow(digits) - 1)
+            })
+            .collect();
+        return Problem { operator, numbers };
     }
 
+    fn next_problems(&mut self, count: usize, max_numbers: usize, max_digits: u32) -> Vec<Problem> {
+        return (0..count)
+            .map(|_| self.next_problem(max_numbers, max_digits))
+            .collect();
+    }
+}
+
+fn part1(contents: &String) -> Option<Answer> {
+    let worksheet = parse_worksheet(contents);
+
+    let expressions: Vec<Expression> = worksheet
+        .row_problems()
+        .iter()
+        .map(Problem::to_expression)
+        .collect();
+
+    dbg!(&expressions);
+
+    return Some(Answer {
+        answer: do_expressions(expressions),
+    });
+}
+
+// Part 1 attempted answers
+// 566612075051 too low
+
+fn part2(contents: &String) -> Option<Answer> {
+    let worksheet = parse_worksheet(contents);
+
+    let expressions: Vec<Expression> = worksheet
+        .column_problems()
+        .unwrap_or_else(|err| panic!("Expected a valid worksheet: {err}"))
+        .iter()
+        .map(Problem::to_expression)
+        .collect();
+
     dbg!(&expressions);
 
     return Some(Answer {
@@ -169,6 +733,30 @@ fn part2(contents: &String) -> Option<Answer> {
 // Part 2 attempted answers
 
 fn main() {
+    // Set to some text to evaluate it instead of the puzzle input
+    let expression: Option<&str> = None;
+    if let Some(text) = expression {
+        let result = parse_expression(text).and_then(|expression| expression.evaluate());
+        println!("{text} = {result:?}");
+        return;
+    }
+
+    let generate_input = false;
+    let right_align = false;
+    if generate_input {
+        let problems = ProblemGenerator::new(2025).next_problems(1000, 4, 4);
+        let options = RenderOptions {
+            padding: 1,
+            alignment: if right_align {
+                Alignment::Right
+            } else {
+                Alignment::Left
+            },
+        };
+        println!("{}", render_worksheet(&problems, &options));
+        return;
+    }
+
     let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
     let result1 = part1(&contents);
     println!("Part1 result {result1:?}");
@@ -211,14 +799,293 @@ mod tests {
         let result = part1(&contents);
 
         dbg!(
-            eval("123*45*6*")
-                .expect("Expected value")
-                .as_f64()
-                .expect("Expected float")
+            parse_expression("123*45*6")
+                .expect("Expected an expression")
+                .evaluate()
+                .expect("Expected a value")
         );
         assert_eq!(result, Some(Answer { answer: 4277556 }));
     }
 
+    #[test]
+    fn test_expression_precedence_and_groups() {
+        let evaluate = |text: &str| parse_expression(text).and_then(|e| e.evaluate());
+        assert_eq!(evaluate("2+3*4"), Ok(14));
+        assert_eq!(evaluate("(2+3)*4"), Ok(20));
+        assert_eq!(evaluate("10-4-3"), Ok(3));
+        assert_eq!(evaluate("100/5/2"), Ok(10));
+        assert_eq!(evaluate("3-(4*2)"), Ok(-5));
+        assert_eq!(
+            evaluate("(1+2"),
+            Err(ExpressionError::UnmatchedParenthesis { position: 0 })
+        );
+        assert_eq!(evaluate("1+"), Err(ExpressionError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_expression_display_brackets() {
+        let number = |value: u64| Box::new(Expression::Number(value));
+        let binary = |operator: Operator, lhs: Box<Expression>, rhs: Box<Expression>| {
+            Box::new(Expression::Binary { operator, lhs, rhs })
+        };
+
+        let cases = [
+            (
+                binary(
+                    Operator::Multiply,
+                    binary(Operator::Add, number(1), number(2)),
+                    number(3),
+                ),
+                "(1+2)*3",
+                9,
+            ),
+            (
+                binary(
+                    Operator::Subtract,
+                    number(10),
+                    binary(Operator::Subtract, number(4), number(3)),
+                ),
+                "10-(4-3)",
+                9,
+            ),
+            (
+                binary(
+                    Operator::Subtract,
+                    binary(Operator::Subtract, number(10), number(4)),
+                    number(3),
+                ),
+                "10-4-3",
+                3,
+            ),
+            (
+                binary(
+                    Operator::Add,
+                    number(1),
+                    binary(Operator::Multiply, number(2), number(3)),
+                ),
+                "1+2*3",
+                7,
+            ),
+        ];
+
+        for (expression, text, value) in cases {
+            assert_eq!(expression.to_string(), text);
+            let parsed = parse_expression(text).expect("Expected to parse");
+            assert_eq!(parsed.evaluate(), Ok(value));
+            assert_eq!(expression.evaluate(), Ok(value));
+            assert_eq!(parsed.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_expression_exact_arithmetic() {
+        // Far past the 2^53 where f64 stops being able to represent every integer
+        let expression = parse_expression("9007199254740993*1000+1").expect("Expected to parse");
+        assert_eq!(expression.evaluate(), Ok(9007199254740993001));
+
+        let overflow = Expression::chain(Operator::Multiply, &[u64::MAX, u64::MAX, u64::MAX]);
+        assert_eq!(
+            overflow.and_then(|e| e.evaluate()),
+            Err(ExpressionError::Overflow)
+        );
+        assert_eq!(
+            parse_expression("7/0").and_then(|e| e.evaluate()),
+            Err(ExpressionError::DivisionByZero)
+        );
+        assert_eq!(
+            parse_expression("7/2").and_then(|e| e.evaluate()),
+            Err(ExpressionError::InexactDivision { lhs: 7, rhs: 2 })
+        );
+    }
+
+    #[test]
+    fn test_worksheet_reading_orders() {
+        let setup = Setup::new();
+        let worksheet = Worksheet::parse(&setup.contents).expect("Expected a worksheet");
+        let problems: Vec<WorksheetProblem> = worksheet.problems().collect();
+
+        assert_eq!(problems.len(), 4);
+        assert_eq!(problems[0].operator(), Operator::Multiply);
+        assert_eq!(problems[3].operator(), Operator::Add);
+        assert_eq!(
+            problems[0].row_numbers().collect::<Vec<u64>>(),
+            vec![123, 45, 6]
+        );
+        assert_eq!(
+            problems[0].column_numbers().collect::<Vec<u64>>(),
+            vec![356, 24, 1]
+        );
+        assert_eq!(
+            problems[3].column_numbers().collect::<Vec<u64>>(),
+            vec![4, 431, 623]
+        );
+    }
+
+    #[test]
+    fn test_worksheet_ragged_lines() {
+        // Trailing spaces stripped, and a trailing blank line
+        let contents = "123 328  51 64\n 45 64  387 23\n  6 98  215 314\n*   +   *   +\n\n";
+        let worksheet = Worksheet::parse(contents).expect("Expected a worksheet");
+        let problems: Vec<WorksheetProblem> = worksheet.problems().collect();
+
+        assert_eq!(problems.len(), 4);
+        assert_eq!(
+            problems[3].row_numbers().collect::<Vec<u64>>(),
+            vec![64, 23, 314]
+        );
+        assert_eq!(
+            problems[3].column_numbers().collect::<Vec<u64>>(),
+            vec![4, 431, 623]
+        );
+        assert_eq!(
+            part1(&contents.to_string()),
+            Some(Answer { answer: 4277556 })
+        );
+        assert_eq!(
+            part2(&contents.to_string()),
+            Some(Answer { answer: 3263827 })
+        );
+    }
+
+    #[test]
+    fn test_worksheet_errors() {
+        assert_eq!(Worksheet::parse(""), Err(WorksheetError::Empty));
+        assert_eq!(
+            Worksheet::parse("12 34\n+"),
+            Err(WorksheetError::MissingOperator { start_column: 3 })
+        );
+        assert_eq!(
+            Worksheet::parse("123\n++"),
+            Err(WorksheetError::MultipleOperators { start_column: 0 })
+        );
+        assert_eq!(
+            Worksheet::parse("1x3\n+"),
+            Err(WorksheetError::UnexpectedCharacter {
+                line: 0,
+                column: 1,
+                character: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn test_worksheet_many_rows() {
+        // More rows than digits fit in a u64 is fine when reading along them
+        let contents = "1\n".repeat(MAX_DIGITS + 1) + "+";
+        let worksheet = Worksheet::parse(&contents).expect("Expected a worksheet");
+        assert_eq!(
+            worksheet.row_problems(),
+            vec![Problem {
+                operator: Operator::Add,
+                numbers: vec![1; MAX_DIGITS + 1],
+            }]
+        );
+        assert_eq!(
+            worksheet.column_problems(),
+            Err(WorksheetError::NumberTooLong { start_column: 0 })
+        );
+
+        // Blank cells don't count towards the length of a column
+        let contents = "1 \n".repeat(MAX_DIGITS) + " 2\n".repeat(5).as_str() + "* ";
+        let worksheet = Worksheet::parse(&contents).expect("Expected a worksheet");
+        assert_eq!(
+            worksheet.column_problems(),
+            Ok(vec![Problem {
+                operator: Operator::Multiply,
+                numbers: vec![22222, 1111111111111111111],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_render_example() {
+        let problems = vec![
+            Problem {
+                operator: Operator::Multiply,
+                numbers: vec![123, 45, 6],
+            },
+            Problem {
+                operator: Operator::Add,
+                numbers: vec![328, 64, 98],
+            },
+            Problem {
+                operator: Operator::Multiply,
+                numbers: vec![51, 387, 215],
+            },
+            Problem {
+                operator: Operator::Add,
+                numbers: vec![64, 23, 314],
+            },
+        ];
+        let options = RenderOptions {
+            padding: 1,
+            alignment: Alignment::Right,
+        };
+        let rendered = render_worksheet(&problems, &options);
+        assert_eq!(
+            rendered,
+            "123 328  51  64\n 45  64 387  23\n  6  98 215 314\n*   +   *   +  "
+        );
+        assert_eq!(part1(&rendered), Some(Answer { answer: 4277556 }));
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let mut generator = ProblemGenerator::new(42);
+        for i in 0..200 {
+            // Small enough that the totals always fit in the answer
+            let problems = generator.next_problems(1 + i % 12, 4, 4);
+            let options = RenderOptions {
+                padding: 1 + i % 3,
+                alignment: if i % 2 == 0 {
+                    Alignment::Left
+                } else {
+                    Alignment::Right
+                },
+            };
+            let rendered = render_worksheet(&problems, &options);
+            let worksheet = Worksheet::parse(&rendered).expect("Expected a worksheet");
+            assert_eq!(worksheet.row_problems(), problems, "Rendered:\n{rendered}");
+
+            let total: u64 = problems
+                .iter()
+                .map(|problem| match problem.operator {
+                    Operator::Multiply => problem.numbers.iter().product::<u64>(),
+                    _ => problem.numbers.iter().sum(),
+                })
+                .sum();
+            assert_eq!(part1(&rendered), Some(Answer { answer: total }));
+            assert!(part2(&rendered).is_some(), "Rendered:\n{rendered}");
+        }
+    }
+
+    #[test]
+    fn test_generator_limits() {
+        let mut generator = ProblemGenerator::new(7);
+        let problems = generator.next_problems(50, 0, 0);
+        assert!(
+            problems
+                .iter()
+                .all(|problem| problem.numbers.len() == 1 && problem.numbers[0] < 10)
+        );
+
+        let problems = generator.next_problems(1000, 4, 4);
+        assert!(
+            problems
+                .iter()
+                .all(|problem| matches!(problem.operator, Operator::Add | Operator::Multiply))
+        );
+        let rendered = render_worksheet(
+            &problems,
+            &RenderOptions {
+                padding: 1,
+                alignment: Alignment::Left,
+            },
+        );
+        assert!(part1(&rendered).is_some());
+        assert!(part2(&rendered).is_some());
+    }
+
     #[test]
     fn test_part1() {
         let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
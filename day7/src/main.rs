@@ -1,4 +1,7 @@
-use std::{collections::HashMap, fs};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
 
 #[derive(Debug, PartialEq)]
 struct Answer {
@@ -19,115 +22,607 @@ impl InputGetter for LocalFileInputGetter {
     }
 }
 
-fn part1(contents: &String) -> Option<Answer> {
-    let mut laser_locations: Vec<usize> = vec![];
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Grid {
+    cells: Vec<Vec<char>>,
+    width: usize,
+}
+
+impl Grid {
+    fn parse(contents: &str) -> Grid {
+        let cells: Vec<Vec<char>> = contents
+            .lines()
+            .map(|line| line.trim_end().chars().collect())
+            .collect();
+        let width = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        return Grid { cells, width };
+    }
+
+    fn height(&self) -> usize {
+        return self.cells.len();
+    }
 
-    let start_location =
-        find_initial_location(contents).expect("Expected the start location to be found");
+    // Rows can be ragged, anything past the end of a row is empty space
+    fn get(&self, row: usize, col: usize) -> char {
+        return self
+            .cells
+            .get(row)
+            .and_then(|cells| cells.get(col))
+            .copied()
+            .unwrap_or('.');
+    }
 
-    let mut split_count = 0;
+    fn find(&self, target: char) -> Option<(usize, usize)> {
+        for (row, cells) in self.cells.iter().enumerate() {
+            if let Some(col) = cells.iter().position(|c| *c == target) {
+                return Some((row, col));
+            }
+        }
+        return None;
+    }
+}
 
-    laser_locations.push(start_location);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
 
-    dbg!(&laser_locations);
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    fn offset(&self) -> (isize, isize) {
+        return match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        };
+    }
 
-    for line in &contents.lines().collect::<Vec<&str>>()[1..] {
-        (laser_locations, split_count) =
-            find_new_locations_part1(line, laser_locations, split_count);
-        dbg!(&laser_locations);
+    fn index(&self) -> usize {
+        return match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+        };
     }
-    // let answer = laser_locations.len() as u64;
+}
 
-    return Some(Answer {
-        answer: split_count as u64,
-    });
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mirror {
+    // '/'
+    Forward,
+    // '\'
+    Back,
 }
 
-fn find_new_locations_part1(
-    line: &str,
-    mut laser_locations: Vec<usize>,
-    mut split_count: usize,
-) -> (Vec<usize>, usize) {
-    for (i, char) in line.chars().enumerate() {
-        if char == '^' && laser_locations.contains(&i) {
-            // Count home many times &i is in there, and do this multiple times
-            laser_locations.push(i - 1);
-            laser_locations.push(i + 1);
-            laser_locations.retain(|x| *x != i);
-            split_count += 1;
-        }
+impl Mirror {
+    fn reflect(&self, direction: Direction) -> Direction {
+        return match (self, direction) {
+            (Mirror::Forward, Direction::Down) => Direction::Left,
+            (Mirror::Forward, Direction::Left) => Direction::Down,
+            (Mirror::Forward, Direction::Up) => Direction::Right,
+            (Mirror::Forward, Direction::Right) => Direction::Up,
+            (Mirror::Back, Direction::Down) => Direction::Right,
+            (Mirror::Back, Direction::Right) => Direction::Down,
+            (Mirror::Back, Direction::Up) => Direction::Left,
+            (Mirror::Back, Direction::Left) => Direction::Up,
+        };
     }
-    return (laser_locations, split_count);
 }
 
-fn find_new_locations_part2(
-    line: &str,
-    mut laser_locations: HashMap<usize, usize>,
-    mut split_count: usize,
-) -> (HashMap<usize, usize>, usize) {
-    for (i, char) in line.chars().enumerate() {
-        if char == '^' && laser_locations.contains_key(&i) {
-            // Count home many times &i is in there, and do this multiple times
-            let location_amount = laser_locations[&i];
-            laser_locations = increment_location(laser_locations, i - 1, location_amount);
-            laser_locations = increment_location(laser_locations, i + 1, location_amount);
-            laser_locations.remove(&i);
-            split_count += 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SplitOutput {
+    // Where the new beam appears, relative to the splitter
+    row_offset: isize,
+    col_offset: isize,
+    direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CellBehaviour {
+    Empty,
+    Absorber,
+    Mirror(Mirror),
+    Splitter {
+        outputs: Vec<SplitOutput>,
+        // Beams travelling in these directions carry on as if the cell were empty
+        pass_through: Vec<Direction>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CellTable {
+    behaviours: HashMap<char, CellBehaviour>,
+}
+
+impl CellTable {
+    // Any character not in the table is treated as empty space
+    fn empty() -> Self {
+        Self {
+            behaviours: HashMap::new(),
         }
     }
-    return (laser_locations, split_count);
+
+    fn tachyon_manifold() -> Self {
+        return CellTable::empty()
+            .with(
+                '^',
+                CellBehaviour::Splitter {
+                    outputs: vec![
+                        SplitOutput {
+                            row_offset: 1,
+                            col_offset: -1,
+                            direction: Direction::Down,
+                        },
+                        SplitOutput {
+                            row_offset: 1,
+                            col_offset: 1,
+                            direction: Direction::Down,
+                        },
+                    ],
+                    pass_through: vec![],
+                },
+            )
+            .with('/', CellBehaviour::Mirror(Mirror::Forward))
+            .with('\\', CellBehaviour::Mirror(Mirror::Back))
+            .with('#', CellBehaviour::Absorber);
+    }
+
+    fn with(mut self, c: char, behaviour: CellBehaviour) -> Self {
+        self.behaviours.insert(c, behaviour);
+        return self;
+    }
+
+    fn get(&self, c: char) -> &CellBehaviour {
+        return self.behaviours.get(&c).unwrap_or(&CellBehaviour::Empty);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BeamState {
+    row: usize,
+    col: usize,
+    direction: Direction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transition {
+    Beam(BeamState),
+    // The beam travelled off the edge of the grid, ending its timeline
+    Exit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SimulationResult {
+    // Number of splitters that were reached by at least one beam
+    splitter_activations: u64,
+    // Number of cells that at least one beam passed through
+    energised_cells: usize,
+    // Number of distinct paths a single particle could have taken, this is
+    // None when the beams can loop forever
+    timelines: Option<u128>,
+    // The beam states making up a loop, if there is one
+    cycle: Option<Vec<BeamState>>,
+    // Only recorded when asked for, and when the beams do not loop
+    trace: Option<SimulationTrace>,
 }
 
-fn find_initial_location(contents: &str) -> Option<usize> {
-    for (i, c) in contents.chars().enumerate() {
-        if c == 'S' {
-            return Some(i);
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SplitterStats {
+    row: usize,
+    col: usize,
+    // Number of timelines that were split by this splitter
+    hits: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SimulationTrace {
+    // Number of timelines passing through each row of the grid. A timeline
+    // only counts once for a row, however many of its states are in it.
+    row_timelines: Vec<u128>,
+    // Every splitter in the grid, in reading order
+    splitters: Vec<SplitterStats>,
+}
+
+impl SimulationTrace {
+    fn unreached_splitters(&self) -> Vec<(usize, usize)> {
+        return self
+            .splitters
+            .iter()
+            .filter(|splitter| splitter.hits == 0)
+            .map(|splitter| (splitter.row, splitter.col))
+            .collect();
+    }
+
+    fn to_table(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(&format!("{:>5} | {:>20}\n", "row", "timelines"));
+        out.push_str(&format!("{:-<5}-+-{:-<20}\n", "", ""));
+        for (row, timelines) in self.row_timelines.iter().enumerate() {
+            out.push_str(&format!("{row:>5} | {timelines:>20}\n"));
+        }
+
+        out.push('\n');
+        out.push_str(&format!("{:>5} | {:>5} | {:>20}\n", "row", "col", "hits"));
+        out.push_str(&format!("{:-<5}-+-{:-<5}-+-{:-<20}\n", "", "", ""));
+        for splitter in &self.splitters {
+            out.push_str(&format!(
+                "{:>5} | {:>5} | {:>20}\n",
+                splitter.row, splitter.col, splitter.hits
+            ));
+        }
+
+        let unreached = self.unreached_splitters();
+        out.push_str(&format!("\n{} splitters never reached\n", unreached.len()));
+        for (row, col) in unreached {
+            out.push_str(&format!("  ({row}, {col})\n"));
+        }
+
+        return out;
+    }
+
+    fn row_timelines_csv(&self) -> String {
+        let mut out = "row,timelines\n".to_string();
+        for (row, timelines) in self.row_timelines.iter().enumerate() {
+            out.push_str(&format!("{row},{timelines}\n"));
         }
+        return out;
+    }
+
+    fn splitters_csv(&self) -> String {
+        let mut out = "row,col,hits,reached\n".to_string();
+        for splitter in &self.splitters {
+            let reached = splitter.hits > 0;
+            out.push_str(&format!(
+                "{},{},{},{reached}\n",
+                splitter.row, splitter.col, splitter.hits
+            ));
+        }
+        return out;
     }
-    None
 }
 
-// Part 1 attempted answers
+struct BeamSimulator<'a> {
+    grid: &'a Grid,
+    table: CellTable,
+    start_direction: Direction,
+    trace: bool,
+}
 
-fn part2(contents: &String) -> Option<Answer> {
-    let mut laser_locations: HashMap<usize, usize> = HashMap::new();
+impl<'a> BeamSimulator<'a> {
+    fn new(grid: &'a Grid) -> Self {
+        Self {
+            grid,
+            table: CellTable::tachyon_manifold(),
+            start_direction: Direction::Down,
+            trace: false,
+        }
+    }
 
-    let start_location =
-        find_initial_location(contents).expect("Expected the start location to be found");
+    // Also record the timelines through every row and splitter, see SimulationTrace
+    fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        return self;
+    }
 
-    let mut split_count = 0;
+    fn with_table(mut self, table: CellTable) -> Self {
+        self.table = table;
+        return self;
+    }
 
-    // laser_locations.push(start_location);
+    fn with_start_direction(mut self, direction: Direction) -> Self {
+        self.start_direction = direction;
+        return self;
+    }
 
-    laser_locations = increment_location(laser_locations, start_location, 1);
+    fn state_index(&self, state: &BeamState) -> usize {
+        return (state.row * self.grid.width + state.col) * Direction::ALL.len()
+            + state.direction.index();
+    }
 
-    dbg!(&laser_locations);
+    fn offset_position(
+        &self,
+        row: usize,
+        col: usize,
+        row_offset: isize,
+        col_offset: isize,
+    ) -> Option<(usize, usize)> {
+        let new_row = row.checked_add_signed(row_offset)?;
+        let new_col = col.checked_add_signed(col_offset)?;
+        if new_row >= self.grid.height() || new_col >= self.grid.width {
+            return None;
+        }
+        return Some((new_row, new_col));
+    }
 
-    for line in &contents.lines().collect::<Vec<&str>>()[1..] {
-        (laser_locations, split_count) =
-            find_new_locations_part2(line, laser_locations, split_count);
-        dbg!(&laser_locations);
+    fn travel(&self, row: usize, col: usize, direction: Direction) -> Transition {
+        let (row_offset, col_offset) = direction.offset();
+        return match self.offset_position(row, col, row_offset, col_offset) {
+            None => Transition::Exit,
+            Some((row, col)) => Transition::Beam(BeamState {
+                row,
+                col,
+                direction,
+            }),
+        };
     }
 
-    let mut answer: u64 = 0;
+    // Whether the beam in this state is split by the cell it is in
+    fn is_split(&self, state: &BeamState) -> bool {
+        return match self.table.get(self.grid.get(state.row, state.col)) {
+            CellBehaviour::Splitter { pass_through, .. } => {
+                !pass_through.contains(&state.direction)
+            }
+            _ => false,
+        };
+    }
 
-    for count in laser_locations.values() {
-        answer += *count as u64;
+    fn transitions(&self, state: &BeamState) -> Vec<Transition> {
+        let BeamState {
+            row,
+            col,
+            direction,
+        } = *state;
+
+        return match self.table.get(self.grid.get(row, col)) {
+            CellBehaviour::Empty => vec![self.travel(row, col, direction)],
+            CellBehaviour::Absorber => vec![],
+            CellBehaviour::Mirror(mirror) => vec![self.travel(row, col, mirror.reflect(direction))],
+            CellBehaviour::Splitter {
+                outputs,
+                pass_through,
+            } => {
+                if pass_through.contains(&direction) {
+                    return vec![self.travel(row, col, direction)];
+                }
+                // Outputs that land off the grid leave it, the same as a
+                // beam travelling off the edge
+                outputs
+                    .iter()
+                    .map(|output| {
+                        match self.offset_position(row, col, output.row_offset, output.col_offset) {
+                            None => Transition::Exit,
+                            Some((row, col)) => Transition::Beam(BeamState {
+                                row,
+                                col,
+                                direction: output.direction,
+                            }),
+                        }
+                    })
+                    .collect()
+            }
+        };
     }
 
-    return Some(Answer { answer });
+    // Depth first search over every reachable beam state, giving them in
+    // topological order, or the loop found if there is not one
+    fn order_states(&self, start: BeamState) -> Result<Vec<BeamState>, Vec<BeamState>> {
+        // 0 is unvisited, 1 is on the current path, 2 is finished
+        let mut marks: Vec<u8> = vec![0; self.grid.width * self.grid.height() * 4];
+        let mut post_order: Vec<BeamState> = vec![];
+        let mut stack: Vec<(BeamState, Vec<Transition>, usize)> = vec![];
+
+        marks[self.state_index(&start)] = 1;
+        stack.push((start, self.transitions(&start), 0));
+
+        while let Some((state, transitions, next)) = stack.last_mut() {
+            let Some(transition) = transitions.get(*next).copied() else {
+                marks[self.state_index(state)] = 2;
+                post_order.push(*state);
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+
+            let Transition::Beam(child) = transition else {
+                continue;
+            };
+
+            match marks[self.state_index(&child)] {
+                0 => {
+                    marks[self.state_index(&child)] = 1;
+                    stack.push((child, self.transitions(&child), 0));
+                }
+                1 => {
+                    let cycle_start = stack
+                        .iter()
+                        .position(|(state, _, _)| *state == child)
+                        .expect("Expected the looping state to be on the stack");
+                    return Err(stack[cycle_start..].iter().map(|(s, _, _)| *s).collect());
+                }
+                _ => {}
+            }
+        }
+
+        post_order.reverse();
+        return Ok(post_order);
+    }
+
+    // Every reachable beam state, without caring about loops
+    fn reachable_states(&self, start: BeamState) -> Vec<BeamState> {
+        let mut seen: Vec<bool> = vec![false; self.grid.width * self.grid.height() * 4];
+        let mut states = vec![start];
+        let mut queue = vec![start];
+        seen[self.state_index(&start)] = true;
+
+        while let Some(state) = queue.pop() {
+            for transition in self.transitions(&state) {
+                if let Transition::Beam(child) = transition
+                    && !seen[self.state_index(&child)]
+                {
+                    seen[self.state_index(&child)] = true;
+                    states.push(child);
+                    queue.push(child);
+                }
+            }
+        }
+
+        return states;
+    }
+
+    fn run(&self) -> Option<SimulationResult> {
+        let (start_row, start_col) = self.grid.find('S')?;
+        let start = BeamState {
+            row: start_row,
+            col: start_col,
+            direction: self.start_direction,
+        };
+
+        let (states, timelines, cycle, trace) = match self.order_states(start) {
+            Ok(states) => {
+                let mut counts: Vec<u128> = vec![0; self.grid.width * self.grid.height() * 4];
+                counts[self.state_index(&start)] = 1;
+                let mut timelines: u128 = 0;
+
+                for state in &states {
+                    let count = counts[self.state_index(state)];
+                    for transition in self.transitions(state) {
+                        match transition {
+                            Transition::Beam(child) => counts[self.state_index(&child)] += count,
+                            Transition::Exit => timelines += count,
+                        }
+                    }
+                }
+                let trace = self
+                    .trace
+                    .then(|| self.build_trace(start, &states, &counts));
+                (states, Some(timelines), None, trace)
+            }
+            Err(cycle) => (self.reachable_states(start), None, Some(cycle), None),
+        };
+
+        let mut energised: HashSet<(usize, usize)> = HashSet::new();
+        let mut activated: HashSet<(usize, usize)> = HashSet::new();
+        for state in &states {
+            energised.insert((state.row, state.col));
+            if self.is_split(state) {
+                activated.insert((state.row, state.col));
+            }
+        }
+
+        return Some(SimulationResult {
+            splitter_activations: activated.len() as u64,
+            energised_cells: energised.len(),
+            timelines,
+            cycle,
+            trace,
+        });
+    }
+
+    fn build_trace(
+        &self,
+        start: BeamState,
+        states: &[BeamState],
+        counts: &[u128],
+    ) -> SimulationTrace {
+        let transitions: Vec<Vec<Transition>> =
+            states.iter().map(|state| self.transitions(state)).collect();
+
+        // A timeline is counted where it first comes into a row, moving in
+        // any direction. It can leave the row and come back later, so for
+        // each row only the timelines that haven't been in it yet are
+        // followed, and they stop once they get there.
+        let mut row_timelines: Vec<u128> = vec![0; self.grid.height()];
+        let mut not_yet_in_row: Vec<u128> = vec![0; counts.len()];
+        for (row, row_count) in row_timelines.iter_mut().enumerate() {
+            if start.row == row {
+                *row_count = 1;
+                continue;
+            }
+            not_yet_in_row.fill(0);
+            not_yet_in_row[self.state_index(&start)] = 1;
+            for (state, transitions) in states.iter().zip(&transitions) {
+                let count = not_yet_in_row[self.state_index(state)];
+                if count == 0 {
+                    continue;
+                }
+                for transition in transitions {
+                    let Transition::Beam(child) = transition else {
+                        continue;
+                    };
+                    if child.row == row {
+                        *row_count += count;
+                    } else {
+                        not_yet_in_row[self.state_index(child)] += count;
+                    }
+                }
+            }
+        }
+
+        let mut hits: HashMap<(usize, usize), u128> = HashMap::new();
+        for state in states {
+            if self.is_split(state) {
+                *hits.entry((state.row, state.col)).or_insert(0) += counts[self.state_index(state)];
+            }
+        }
+
+        let mut splitters = vec![];
+        for row in 0..self.grid.height() {
+            for col in 0..self.grid.width {
+                if let CellBehaviour::Splitter { .. } = self.table.get(self.grid.get(row, col)) {
+                    splitters.push(SplitterStats {
+                        row,
+                        col,
+                        hits: hits.get(&(row, col)).copied().unwrap_or(0),
+                    });
+                }
+            }
+        }
+
+        return SimulationTrace {
+            row_timelines,
+            splitters,
+        };
+    }
+}
+
+fn write_trace(trace: &SimulationTrace) -> std::io::Result<()> {
+    fs::write("row_timelines.csv", trace.row_timelines_csv())?;
+    fs::write("splitters.csv", trace.splitters_csv())?;
+    Ok(())
+}
+
+fn simulate(contents: &str) -> SimulationResult {
+    let grid = Grid::parse(contents);
+    // The same as the defaults, spelled out so another beam puzzle only has
+    // to swap in its own table and start direction
+    return BeamSimulator::new(&grid)
+        .with_table(CellTable::tachyon_manifold())
+        .with_start_direction(Direction::Down)
+        .run()
+        .expect("Expected the start location to be found");
 }
 
-fn increment_location(
-    mut laser_locations: HashMap<usize, usize>,
-    location: usize,
-    amount: usize,
-) -> HashMap<usize, usize> {
-    laser_locations
-        .entry(location)
-        .and_modify(|counter| *counter += amount)
-        .or_insert(amount);
-    return laser_locations;
+fn part1(contents: &String) -> Option<Answer> {
+    let result = simulate(contents);
+
+    return Some(Answer {
+        answer: result.splitter_activations,
+    });
+}
+
+// Part 1 attempted answers
+
+fn part2(contents: &String) -> Option<Answer> {
+    let result = simulate(contents);
+
+    let timelines = result
+        .timelines
+        .expect("Expected the beams not to loop forever");
+    let answer = u64::try_from(timelines).expect("Expected the timelines to fit in a u64");
+
+    return Some(Answer { answer });
 }
 
 // Part 2 attempted answers
@@ -135,6 +630,21 @@ fn increment_location(
 
 fn main() {
     let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
+
+    let show_trace = false;
+    if show_trace {
+        let grid = Grid::parse(&contents);
+        let result = BeamSimulator::new(&grid)
+            .with_trace(true)
+            .run()
+            .expect("Expected the start location to be found");
+        let trace = result
+            .trace
+            .expect("Expected the beams not to loop forever");
+        println!("{}", trace.to_table());
+        write_trace(&trace).expect("Expected to write the trace files");
+    }
+
     let result1 = part1(&contents);
     println!("Part1 result {result1:?}");
 
@@ -189,6 +699,221 @@ mod tests {
         assert_eq!(result, Some(Answer { answer: 21 }));
     }
 
+    #[test]
+    fn test_simulator_single_pass() {
+        let setup = Setup::new();
+        let result = simulate(&setup.contents);
+        assert_eq!(
+            result,
+            SimulationResult {
+                splitter_activations: 21,
+                energised_cells: 74,
+                timelines: Some(40),
+                cycle: None,
+                trace: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_simulator_edge_cases() {
+        // The start is not on the first line, and the splitters sit on the
+        // edges of the grid, so beams leave on both sides and count as
+        // timelines the same as the ones leaving the bottom
+        let contents = "...\n\
+                        S..\n\
+                        ^..\n\
+                        .^.\n\
+                        ..^\n\
+                        ...";
+        let grid = Grid::parse(contents);
+        assert_eq!(grid.find('S'), Some((1, 0)));
+
+        let result = BeamSimulator::new(&grid)
+            .run()
+            .expect("Expected a start location");
+        assert_eq!(result.splitter_activations, 3);
+        assert_eq!(result.timelines, Some(4));
+
+        assert_eq!(BeamSimulator::new(&Grid::parse("...\n.^.")).run(), None);
+    }
+
+    #[test]
+    fn test_simulator_splitter_on_edge() {
+        // One output is off the left of the grid, the other off the bottom
+        let result = simulate("S..\n^..");
+        assert_eq!(result.splitter_activations, 1);
+        assert_eq!(result.timelines, Some(2));
+        assert_eq!(result.energised_cells, 2);
+    }
+
+    #[test]
+    fn test_simulator_splitter_on_last_row() {
+        let result = simulate(".S.\n...\n.^.");
+        assert_eq!(result.splitter_activations, 1);
+        assert_eq!(result.timelines, Some(2));
+    }
+
+    #[test]
+    fn test_simulator_mirrors_and_absorbers() {
+        // The beam is sent right, down, left, and then back up into the absorber
+        let rows = [".S...", ".....", r".\..\", ".#...", r"#\../"];
+        let grid = Grid::parse(&rows.join("\n"));
+        let result = BeamSimulator::new(&grid)
+            .run()
+            .expect("Expected a start location");
+        assert_eq!(result.timelines, Some(0));
+        assert_eq!(result.cycle, None);
+        assert_eq!(result.energised_cells, 12);
+
+        // Without the absorber the beam gets out of the left side
+        let grid = Grid::parse(&rows.join("\n").replace(".#...", "....."));
+        let result = BeamSimulator::new(&grid)
+            .run()
+            .expect("Expected a start location");
+        assert_eq!(result.timelines, Some(1));
+    }
+
+    #[test]
+    fn test_simulator_cycle_detection() {
+        let rows = [r"/.\", "S..", r"\./"];
+        let grid = Grid::parse(&rows.join("\n"));
+        let result = BeamSimulator::new(&grid)
+            .with_start_direction(Direction::Up)
+            .run()
+            .expect("Expected a start location");
+        assert_eq!(result.timelines, None);
+        let cycle = result.cycle.expect("Expected a cycle");
+        assert_eq!(cycle.len(), 8);
+        assert!(cycle.contains(&BeamState {
+            row: 0,
+            col: 2,
+            direction: Direction::Right
+        }));
+    }
+
+    #[test]
+    fn test_simulator_custom_splitters() {
+        // A splitter that only splits sideways moving beams, sending them up
+        // and down, with everything else passing through
+        let table = CellTable::empty().with(
+            '|',
+            CellBehaviour::Splitter {
+                outputs: vec![
+                    SplitOutput {
+                        row_offset: -1,
+                        col_offset: 0,
+                        direction: Direction::Up,
+                    },
+                    SplitOutput {
+                        row_offset: 1,
+                        col_offset: 0,
+                        direction: Direction::Down,
+                    },
+                ],
+                pass_through: vec![Direction::Up, Direction::Down],
+            },
+        );
+        let grid = Grid::parse("...\nS|.\n...");
+        let result = BeamSimulator::new(&grid)
+            .with_table(table)
+            .with_start_direction(Direction::Right)
+            .run()
+            .expect("Expected a start location");
+        assert_eq!(result.splitter_activations, 1);
+        assert_eq!(result.timelines, Some(2));
+        assert_eq!(result.energised_cells, 4);
+    }
+
+    #[test]
+    fn test_simulator_trace_with_mirrors() {
+        // The beam turns right along the middle row, then back down
+        let rows = ["S...", r"\..\", "...."];
+        let grid = Grid::parse(&rows.join("\n"));
+        let result = BeamSimulator::new(&grid)
+            .with_trace(true)
+            .run()
+            .expect("Expected a start location");
+        assert_eq!(result.timelines, Some(1));
+
+        let trace = result.trace.expect("Expected a trace");
+        assert_eq!(trace.row_timelines, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_simulator_trace_sideways_split() {
+        // The splitter sends the beam into the next row already moving right
+        let table = CellTable::tachyon_manifold().with(
+            '>',
+            CellBehaviour::Splitter {
+                outputs: vec![SplitOutput {
+                    row_offset: 1,
+                    col_offset: 0,
+                    direction: Direction::Right,
+                }],
+                pass_through: vec![],
+            },
+        );
+        let grid = Grid::parse("S..\n>..\n...");
+        let result = BeamSimulator::new(&grid)
+            .with_table(table)
+            .with_trace(true)
+            .run()
+            .expect("Expected a start location");
+        assert_eq!(result.timelines, Some(1));
+
+        let trace = result.trace.expect("Expected a trace");
+        assert_eq!(trace.row_timelines, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_simulator_trace_row_revisited() {
+        // The beam goes back up into the first row and down again, but is
+        // still only one timeline through each row
+        let rows = [r"S./\", r"\./.", "...."];
+        let grid = Grid::parse(&rows.join("\n"));
+        let result = BeamSimulator::new(&grid)
+            .with_trace(true)
+            .run()
+            .expect("Expected a start location");
+        assert_eq!(result.timelines, Some(1));
+
+        let trace = result.trace.expect("Expected a trace");
+        assert_eq!(trace.row_timelines, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_simulator_trace() {
+        let setup = Setup::new();
+        let grid = Grid::parse(&setup.contents);
+        let result = BeamSimulator::new(&grid)
+            .with_trace(true)
+            .run()
+            .expect("Expected a start location");
+        let trace = result.trace.expect("Expected a trace");
+
+        assert_eq!(trace.row_timelines[0], 1);
+        assert_eq!(trace.row_timelines[3], 2);
+        assert_eq!(trace.row_timelines.last(), Some(&40));
+        assert_eq!(trace.splitters.len(), 22);
+        assert_eq!(
+            trace.splitters[0],
+            SplitterStats {
+                row: 2,
+                col: 7,
+                hits: 1
+            }
+        );
+        // The only splitter that no beam gets to
+        assert_eq!(trace.unreached_splitters(), vec![(14, 9)]);
+
+        let csv = trace.splitters_csv();
+        assert!(csv.starts_with("row,col,hits,reached\n2,7,1,true\n"));
+        assert!(csv.contains("14,9,0,false\n"));
+        assert!(trace.row_timelines_csv().ends_with("15,40\n"));
+        assert!(trace.to_table().contains("1 splitters never reached"));
+    }
+
     #[test]
     fn test_part1() {
         let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
@@ -1,7 +1,11 @@
-#![feature(core_float_math)]
-use core::f32::math::sqrt;
-use std::{cmp::Ordering, fmt, fs::{self, File}};
+use geometry::Point;
 use std::io::prelude::*;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap},
+    fmt,
+    fs::{self, File},
+};
 
 #[derive(Debug, PartialEq)]
 struct Answer {
@@ -22,54 +26,41 @@ impl InputGetter for LocalFileInputGetter {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash)]
-struct Location {
-    x: i64,
-    y: i64,
-    z: i64,
-}
-
-impl Location {
-    fn calculate_distance(&self, other_location: &Location) -> f32 {
-        let x_diff = self.x - other_location.x;
-        let y_diff = self.y - other_location.y;
-        let z_diff = self.z - other_location.z;
-        return sqrt((x_diff.pow(2) + y_diff.pow(2) + z_diff.pow(2)) as f32);
-    }
-}
-
 #[derive(Eq, PartialEq, Clone, Hash)]
 struct LocationPair {
-    loc1: Location,
-    loc2: Location,
+    loc1: Point<3>,
+    loc2: Point<3>,
+    // Indexes of the locations in the parsed input
+    index1: usize,
+    index2: usize,
 }
 
 impl LocationPair {
-    fn calculate_distance(&self) -> f32 {
-        return self.loc1.calculate_distance(&self.loc2);
+    fn squared_distance(&self) -> i128 {
+        return self.loc1.squared_euclidean_distance(&self.loc2);
     }
 
-    fn get_locations(&self) -> Vec<Location> {
-        return vec![self.loc1.clone(), self.loc2.clone()];
-    }
-
-    fn swap_locations(&self) -> Self {
-        return LocationPair {
-            loc1: self.loc2.clone(),
-            loc2: self.loc1.clone(),
-        };
+    // Used to break ties between pairs the same distance apart, so the order
+    // never depends on how the pairs were generated
+    fn sort_key(&self) -> (i128, usize, usize, usize) {
+        return (
+            self.squared_distance(),
+            self.index1.min(self.index2),
+            self.index1.max(self.index2),
+            self.index1,
+        );
     }
 }
 
 impl Ord for LocationPair {
     fn cmp(&self, other: &Self) -> Ordering {
-        (self.calculate_distance()).total_cmp(&other.calculate_distance())
+        self.sort_key().cmp(&other.sort_key())
     }
 }
 
 impl PartialOrd for LocationPair {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some((self.calculate_distance()).total_cmp(&other.calculate_distance()))
+        Some(self.cmp(other))
     }
 }
 
@@ -78,275 +69,568 @@ impl fmt::Debug for LocationPair {
         f.debug_struct("LocationPair")
             .field("\n  loc1", &self.loc1)
             .field("\n  loc2", &self.loc2)
-            .field("\n  .calculate_distance()", &self.calculate_distance())
+            .field("\n  .squared_distance()", &self.squared_distance())
             .finish()
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Clone)]
-struct LocationCluster {
-    locations: Vec<Location>,
-    // Pairs is mainly there to assist with debugging where the locations from from
-    pairs: Vec<LocationPair>,
+// Union-find over location indexes, each set is one circuit of junction boxes
+#[derive(Debug, Clone)]
+struct DisjointSet {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    components: usize,
+    // The pair of indexes whose union left everything in one set
+    final_merge: Option<(usize, usize)>,
 }
 
-impl LocationCluster {
-    fn check_connected(&self, pair: &LocationPair) -> bool {
-        return self.locations.contains(&pair.loc1) || self.locations.contains(&pair.loc2);
+impl DisjointSet {
+    fn new(size: usize) -> Self {
+        Self {
+            parents: (0..size).collect(),
+            sizes: vec![1; size],
+            components: size,
+            final_merge: None,
+        }
     }
 
-    fn add_pair(&mut self, pair: &LocationPair) {
-        for location in &pair.get_locations() {
-            if !self.locations.contains(location) {
-                self.locations.push(location.clone());
-            }
+    fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
         }
-        self.pairs.push(pair.clone());
-    }
 
-    fn combine(&mut self, cluster: &LocationCluster) {
-        for location in &cluster.locations {
-            if !self.locations.contains(location) {
-                self.locations.push(location.clone());
-            }
+        // Path compression, point everything on the way straight at the root
+        let mut current = index;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
         }
-        self.pairs.extend(cluster.pairs.clone());
+
+        return root;
     }
-}
 
-impl fmt::Debug for LocationCluster {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("LocationCluster")
-            .field("locations.len()", &self.locations.len())
-            .field("locations", &self.locations)
-            .finish()
+    // Returns false if the two were already in the same set
+    fn union(&mut self, index1: usize, index2: usize) -> bool {
+        let mut root1 = self.find(index1);
+        let mut root2 = self.find(index2);
+        if root1 == root2 {
+            return false;
+        }
+
+        // Union by size, hang the smaller tree off the larger one
+        if self.sizes[root1] < self.sizes[root2] {
+            (root1, root2) = (root2, root1);
+        }
+        self.parents[root2] = root1;
+        self.sizes[root1] += self.sizes[root2];
+        self.components -= 1;
+
+        if self.components == 1 {
+            self.final_merge = Some((index1, index2));
+        }
+
+        return true;
     }
-}
 
-fn parse_coords(line: &str) -> (i64, i64, i64) {
-    let split_line: Vec<&str> = line.split(",").collect();
+    fn components(&self) -> usize {
+        return self.components;
+    }
 
-    return (
-        split_line[0].parse().expect("Expected a value here"),
-        split_line[1].parse().expect("Expected a value here"),
-        split_line[2].parse().expect("Expected a value here"),
-    );
+    // Sizes of every set, largest first
+    fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = (0..self.parents.len())
+            .filter(|index| self.parents[*index] == *index)
+            .map(|root| self.sizes[root])
+            .collect();
+        sizes.sort_by(|size1, size2| size2.cmp(size1));
+        return sizes;
+    }
 }
 
-fn parse_locations(contents: &String) -> Vec<Location> {
-    let mut locations: Vec<Location> = vec![];
+fn parse_locations(contents: &String) -> Vec<Point<3>> {
+    let mut locations: Vec<Point<3>> = vec![];
     for line in contents.lines() {
-        let (x, y, z) = parse_coords(line);
-        locations.push(Location { x, y, z })
+        locations.push(line.parse().expect("Expected a location here"))
     }
     return locations;
 }
 
-fn create_pairs(locations: &Vec<Location>) -> Vec<LocationPair> {
-    // take only the x closest pairs, where x is a value that we can tune
-    let max_closest_per_location = 10;
-    let mut closest_pairs: Vec<LocationPair> = vec![];
-    for location in locations {
-        let mut this_location_pairs: Vec<LocationPair> = vec![];
-        for other_location in locations {
-            if location == other_location {
-                continue;
-            }
-
-            this_location_pairs.push(LocationPair {
-                loc1: location.clone(),
-                loc2: other_location.clone(),
-            });
-        }
+// k-d tree over the locations, stored as a permutation of the location
+// indexes where the median of each range splits it on the axis for that depth
+struct KdTree<'a> {
+    locations: &'a [Point<3>],
+    order: Vec<usize>,
+}
 
-        this_location_pairs.sort();
-        let only_closest_pairs = this_location_pairs[..max_closest_per_location].to_vec();
-        closest_pairs.extend(only_closest_pairs)
+impl<'a> KdTree<'a> {
+    fn new(locations: &'a [Point<3>]) -> Self {
+        let mut order: Vec<usize> = (0..locations.len()).collect();
+        KdTree::build(locations, &mut order, 0);
+        Self { locations, order }
+    }
 
+    fn build(locations: &[Point<3>], order: &mut [usize], depth: usize) {
+        if order.len() <= 1 {
+            return;
+        }
+        let axis = depth % 3;
+        let mid = order.len() / 2;
+        order.select_nth_unstable_by_key(mid, |index| locations[*index].coords[axis]);
+        let (lower, upper) = order.split_at_mut(mid);
+        KdTree::build(locations, lower, depth + 1);
+        KdTree::build(locations, &mut upper[1..], depth + 1);
     }
-    closest_pairs.sort();
 
-    return closest_pairs;
-}
+    // The k nearest locations to `index` that have a larger index than it, as
+    // (squared distance, index), closest first with ties going to the lower index
+    fn nearest_after(&self, index: usize, k: usize) -> Vec<(i128, usize)> {
+        let mut best: BinaryHeap<(i128, usize)> = BinaryHeap::new();
+        self.search(index, k, 0, self.order.len(), 0, &mut best);
+        return best.into_sorted_vec();
+    }
 
-fn add_pairs_to_clusters(
-    clusters: Vec<LocationCluster>,
-    pair: &LocationPair,
-) -> Vec<LocationCluster> {
-    // Loop through each cluster in clusters to see which the pair belongs
-    // If it belongs in multiple them combine the clusters
+    fn search(
+        &self,
+        index: usize,
+        k: usize,
+        low: usize,
+        high: usize,
+        depth: usize,
+        best: &mut BinaryHeap<(i128, usize)>,
+    ) {
+        if low >= high || k == 0 {
+            return;
+        }
 
-    let mut connected: Vec<LocationCluster> = vec![];
-    let mut out_clusters: Vec<LocationCluster> = vec![];
+        let target = &self.locations[index];
+        let axis = depth % 3;
+        let mid = low + (high - low) / 2;
+        let node = self.order[mid];
+
+        if node > index {
+            let candidate = (
+                target.squared_euclidean_distance(&self.locations[node]),
+                node,
+            );
+            if best.len() < k {
+                best.push(candidate);
+            } else if best.peek().is_some_and(|worst| candidate < *worst) {
+                best.pop();
+                best.push(candidate);
+            }
+        }
 
-    for cluster in &clusters {
-        if cluster.check_connected(pair) {
-            connected.push(cluster.clone())
+        let plane_diff = target.coords[axis] as i128 - self.locations[node].coords[axis] as i128;
+        let (near, far) = if plane_diff < 0 {
+            ((low, mid), (mid + 1, high))
         } else {
-            out_clusters.push(cluster.clone())
+            ((mid + 1, high), (low, mid))
+        };
+
+        self.search(index, k, near.0, near.1, depth + 1, best);
+
+        // Only bother with the other side if something there could still be
+        // closer (or equally close, with a lower index) than the worst kept
+        let worst = best.peek().map(|worst| worst.0);
+        if best.len() < k || worst.is_some_and(|worst| plane_diff.pow(2) <= worst) {
+            self.search(index, k, far.0, far.1, depth + 1, best);
         }
     }
+}
 
-    match connected.len() {
-        0 => {
-            // println!("New cluster for {pair:?}");
-            out_clusters.push(LocationCluster {
-                locations: pair.get_locations(),
-                pairs: vec![pair.clone()],
-            });
-        }
-        1 => {
-            // println!("Add to cluster for {pair:?}");
-            let cluster: &mut LocationCluster =
-                connected.get_mut(0).expect("Expected a cluster here");
-            cluster.add_pair(pair);
-            // dbg!(&cluster);
-            out_clusters.push(cluster.clone());
-        }
-        _ => {
-            // println!("Combine clusters for {pair:?} {connected:?}");
+// Streams every pair of locations exactly once, in increasing order of
+// distance. Each location keeps a growing buffer of its nearest neighbours
+// with a larger index, and a heap merges the heads of all the buffers.
+struct ClosestPairs<'a> {
+    tree: KdTree<'a>,
+    neighbours: Vec<Vec<(i128, usize)>>,
+    // How many neighbours were asked for when each buffer was filled
+    requested: Vec<usize>,
+    positions: Vec<usize>,
+    heap: BinaryHeap<Reverse<(i128, usize, usize)>>,
+}
 
-            let mut new_combo_cluster = LocationCluster {
-                locations: pair.get_locations(),
-                pairs: vec![pair.clone()],
-            };
+impl<'a> ClosestPairs<'a> {
+    const INITIAL_NEIGHBOURS: usize = 4;
 
-            for cluster in &connected {
-                new_combo_cluster.combine(cluster);
+    fn new(locations: &'a [Point<3>]) -> Self {
+        let tree = KdTree::new(locations);
+        let mut neighbours = vec![];
+        let mut heap = BinaryHeap::new();
+
+        for index in 0..locations.len() {
+            let nearest = tree.nearest_after(index, ClosestPairs::INITIAL_NEIGHBOURS);
+            if let Some((distance, other)) = nearest.first() {
+                heap.push(Reverse((*distance, index, *other)));
             }
-            out_clusters.push(new_combo_cluster);
+            neighbours.push(nearest);
+        }
+
+        Self {
+            tree,
+            requested: vec![ClosestPairs::INITIAL_NEIGHBOURS; locations.len()],
+            positions: vec![0; locations.len()],
+            neighbours,
+            heap,
         }
     }
 
-    return out_clusters;
-}
+    fn advance(&mut self, index: usize) {
+        self.positions[index] += 1;
+        let position = self.positions[index];
+        let buffer_len = self.neighbours[index].len();
 
-fn remove_duplicates(closest_pairs: Vec<LocationPair>) -> Vec<LocationPair> {
-    let mut out_pairs: Vec<LocationPair> = vec![];
-    let pairs_len = closest_pairs.len();
-    for (i, pair) in closest_pairs.iter().enumerate() {
-        if i % 10000 == 0 {
-            println!("Currently on pair {i}/{pairs_len}")
+        if position == buffer_len {
+            // A short buffer means there was nothing more to find
+            if buffer_len < self.requested[index] {
+                return;
+            }
+            self.requested[index] *= 2;
+            self.neighbours[index] = self.tree.nearest_after(index, self.requested[index]);
         }
-        if !out_pairs.contains(&pair.swap_locations()) {
-            out_pairs.push(pair.clone())
+
+        if let Some((distance, other)) = self.neighbours[index].get(position) {
+            self.heap.push(Reverse((*distance, index, *other)));
         }
     }
-    return out_pairs;
 }
 
-fn get_three_largest_clusters(mut clusters: Vec<LocationCluster>) -> Vec<LocationCluster> {
-    // let mut size_map: HashMap<&LocationCluster, usize> = HashMap::new();
+impl Iterator for ClosestPairs<'_> {
+    type Item = LocationPair;
 
-    // for cluster in &clusters {
-    //     size_map.insert(cluster, cluster.locations.len());
-    // }
+    fn next(&mut self) -> Option<LocationPair> {
+        let Reverse((_, index1, index2)) = self.heap.pop()?;
+        self.advance(index1);
 
-    // size_map.sor
-
-    clusters.sort_by(|c1, c2| c1.locations.len().cmp(&c2.locations.len()));
+        let locations = self.tree.locations;
+        return Some(LocationPair {
+            loc1: locations[index1],
+            loc2: locations[index2],
+            index1,
+            index2,
+        });
+    }
+}
 
-    let out_clusters: Vec<LocationCluster> = clusters[clusters.len() - 3..].to_vec();
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SpanningEdge {
+    pair: LocationPair,
+    // Position of this pair among every pair sorted by distance, so the pairs
+    // that were skipped for joining an existing circuit are still counted
+    connection: usize,
+}
 
-    return out_clusters;
+// One step of single-linkage clustering. Locations are clusters 0..n, and the
+// cluster made by the i-th merge is n + i
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MergeEvent {
+    left: usize,
+    right: usize,
+    merged: usize,
+    size: usize,
 }
 
-fn _write_to_file(text: &String) -> std::io::Result<()> {
-    let mut file = File::create("closest_pairs.txt")?;
-    file.write_all(text.as_bytes())?;
-    Ok(())
+#[derive(Debug, Clone)]
+struct SpanningTree {
+    location_count: usize,
+    // In the order they were added, which is also increasing distance
+    edges: Vec<SpanningEdge>,
+    dendrogram: Vec<MergeEvent>,
 }
 
+impl SpanningTree {
+    fn total_weight(&self) -> f64 {
+        return self
+            .edges
+            .iter()
+            .map(|edge| (edge.pair.squared_distance() as f64).sqrt())
+            .sum();
+    }
 
-fn get_all_pairs(contents: &String) -> (Vec<Location>, Vec<LocationPair>) {
-    println!("Parsing locations");
-    let locations = parse_locations(contents);
+    // The circuits formed once the closest `connections` pairs have been joined
+    fn clusters_after(&self, connections: usize) -> DisjointSet {
+        let mut circuits = DisjointSet::new(self.location_count);
+        for edge in self
+            .edges
+            .iter()
+            .take_while(|edge| edge.connection < connections)
+        {
+            circuits.union(edge.pair.index1, edge.pair.index2);
+        }
+        return circuits;
+    }
 
-    println!("Creating closest pairs");
-    let mut closest_pairs = create_pairs(&locations);
+    fn top_cluster_sizes(&self, connections: usize, n: usize) -> Vec<usize> {
+        let mut sizes = self.clusters_after(connections).component_sizes();
+        sizes.truncate(n);
+        return sizes;
+    }
 
-    println!("Removing duplicates");
-    closest_pairs = remove_duplicates(closest_pairs);
+    // The edge that finally joins everything into one circuit
+    fn last_connecting_edge(&self) -> Option<&SpanningEdge> {
+        if self.edges.len() + 1 != self.location_count {
+            return None;
+        }
+        return self.edges.last();
+    }
+}
 
-    println!("Writing debug to file");
-    // let debug_string: String = "".to_string();
-    // let debug_string: String = closest_pairs.iter().map(|p| format!("{p:?}\n")).collect();
-    // let _ = write_to_file(&debug_string);
+// Kruskal's algorithm, taking pairs closest first until everything is connected
+fn minimum_spanning_tree(locations: &[Point<3>]) -> SpanningTree {
+    let mut circuits = DisjointSet::new(locations.len());
+    let mut cluster_ids: Vec<usize> = (0..locations.len()).collect();
+    let mut edges = vec![];
+    let mut dendrogram = vec![];
+
+    for (connection, pair) in ClosestPairs::new(locations).enumerate() {
+        let left = cluster_ids[circuits.find(pair.index1)];
+        let right = cluster_ids[circuits.find(pair.index2)];
+        if !circuits.union(pair.index1, pair.index2) {
+            continue;
+        }
 
+        let root = circuits.find(pair.index1);
+        let merged = locations.len() + dendrogram.len();
+        cluster_ids[root] = merged;
+        dendrogram.push(MergeEvent {
+            left,
+            right,
+            merged,
+            size: circuits.sizes[root],
+        });
+        edges.push(SpanningEdge { pair, connection });
+
+        if circuits.final_merge.is_some() {
+            break;
+        }
+    }
 
-    dbg!(&closest_pairs);
+    return SpanningTree {
+        location_count: locations.len(),
+        edges,
+        dendrogram,
+    };
+}
 
-    return (locations, closest_pairs);
+const CLUSTER_COLOURS: [&str; 10] = [
+    "#e6194b", "#3cb44b", "#4363d8", "#f58231", "#911eb4", "#42d4f4", "#f032e6", "#bfef45",
+    "#469990", "#9a6324",
+];
+const SINGLE_LOCATION_COLOUR: &str = "#a9a9a9";
+
+// The circuits after some number of connections, ready to be written out for
+// external tools. Cluster 0 is the largest, ties going to the lowest location index
+struct ClusterExport<'a> {
+    locations: &'a [Point<3>],
+    cluster_ids: Vec<usize>,
+    // The location indexes in each cluster, in order
+    clusters: Vec<Vec<usize>>,
+    edges: Vec<&'a SpanningEdge>,
 }
 
-fn part1(contents: &String, num_connections: usize) -> Option<Answer> {
-    let (_, closest_pairs) = get_all_pairs(contents);
-    let mut clusters: Vec<LocationCluster> = vec![];
+impl SpanningTree {
+    fn export<'a>(&'a self, locations: &'a [Point<3>], connections: usize) -> ClusterExport<'a> {
+        let mut circuits = self.clusters_after(connections);
+        let roots: Vec<usize> = (0..locations.len())
+            .map(|index| circuits.find(index))
+            .collect();
+
+        // Roots in order of their lowest location index
+        let mut ordered_roots: Vec<usize> = vec![];
+        let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (index, root) in roots.iter().enumerate() {
+            members
+                .entry(*root)
+                .or_insert_with(|| {
+                    ordered_roots.push(*root);
+                    vec![]
+                })
+                .push(index);
+        }
+        // Stable, so equal sizes stay in order of their lowest location index
+        ordered_roots.sort_by(|root1, root2| circuits.sizes[*root2].cmp(&circuits.sizes[*root1]));
 
-    println!("Starting clustering");
+        let mut root_ids: HashMap<usize, usize> = HashMap::new();
+        for (id, root) in ordered_roots.iter().enumerate() {
+            root_ids.insert(*root, id);
+        }
 
-    println!("Finding clusters with the closest {num_connections} connections");
-    for pair in &closest_pairs[..num_connections] {
-        println!("\nFinding cluster for {pair:?}");
-        clusters = add_pairs_to_clusters(clusters, pair);
+        return ClusterExport {
+            locations,
+            cluster_ids: roots.iter().map(|root| root_ids[root]).collect(),
+            clusters: ordered_roots
+                .iter()
+                .map(|root| {
+                    members
+                        .remove(root)
+                        .expect("Expected every root to have members")
+                })
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .take_while(|edge| edge.connection < connections)
+                .collect(),
+        };
     }
+}
 
-    let three_largest_clusters: Vec<LocationCluster> = get_three_largest_clusters(clusters);
-
-    let mut answer = three_largest_clusters
-        .get(0)
-        .expect("Expected a cluster here")
-        .locations
-        .len() as u64;
+impl ClusterExport<'_> {
+    fn colour(&self, cluster_id: usize) -> &'static str {
+        if self.clusters[cluster_id].len() == 1 {
+            return SINGLE_LOCATION_COLOUR;
+        }
+        return CLUSTER_COLOURS[cluster_id % CLUSTER_COLOURS.len()];
+    }
 
-    dbg!(&three_largest_clusters);
+    fn to_dot(&self) -> String {
+        let mut out = "graph junction_boxes {\n    node [style=filled];\n".to_string();
+
+        // Graphviz only draws a box round subgraphs named cluster_...,
+        // locations on their own are left outside of any box
+        for (cluster_id, members) in self.clusters.iter().enumerate() {
+            let indent = if members.len() > 1 {
+                out.push_str(&format!(
+                    "    subgraph cluster_{cluster_id} {{\n        label=\"cluster {cluster_id}, size {}\";\n",
+                    members.len()
+                ));
+                "        "
+            } else {
+                "    "
+            };
 
-    for cluster in &three_largest_clusters[1..] {
-        answer *= cluster.locations.len() as u64;
-    }
+            for index in members {
+                let location = self.locations[*index];
+                out.push_str(&format!(
+                    "{indent}n{index} [label=\"{},{},{}\", fillcolor=\"{}\"];\n",
+                    location.x(),
+                    location.y(),
+                    location.z(),
+                    self.colour(cluster_id)
+                ));
+            }
 
-    Some(Answer { answer })
-}
+            if members.len() > 1 {
+                out.push_str("    }\n");
+            }
+        }
 
-// Part 1 attempted answers
+        for edge in &self.edges {
+            let pair = &edge.pair;
+            let distance = (pair.squared_distance() as f64).sqrt();
+            out.push_str(&format!(
+                "    n{} -- n{} [label=\"{distance:.3}\", color=\"{}\"];\n",
+                pair.index1,
+                pair.index2,
+                self.colour(self.cluster_ids[pair.index1])
+            ));
+        }
 
-fn part2(contents: &String) -> Option<Answer> {
+        out.push_str("}\n");
+        return out;
+    }
 
-    let (locations, closest_pairs) = get_all_pairs(contents);
+    fn to_json(&self) -> String {
+        let locations: Vec<String> = self
+            .locations
+            .iter()
+            .enumerate()
+            .map(|(index, location)| {
+                format!(
+                    "    {{\"index\": {index}, \"x\": {}, \"y\": {}, \"z\": {}, \"cluster\": {}}}",
+                    location.x(),
+                    location.y(),
+                    location.z(),
+                    self.cluster_ids[index]
+                )
+            })
+            .collect();
+
+        let clusters: Vec<String> = self
+            .clusters
+            .iter()
+            .enumerate()
+            .map(|(id, members)| {
+                let members: Vec<String> = members.iter().map(|index| index.to_string()).collect();
+                format!(
+                    "    {{\"id\": {id}, \"size\": {}, \"locations\": [{}]}}",
+                    members.len(),
+                    members.join(", ")
+                )
+            })
+            .collect();
+
+        let edges: Vec<String> = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let pair = &edge.pair;
+                let squared_distance = pair.squared_distance();
+                let distance = (squared_distance as f64).sqrt();
+                format!(
+                    "    {{\"from\": {}, \"to\": {}, \"connection\": {}, \"squared_distance\": {squared_distance}, \"distance\": {distance}, \"cluster\": {}}}",
+                    pair.index1,
+                    pair.index2,
+                    edge.connection,
+                    self.cluster_ids[pair.index1]
+                )
+            })
+            .collect();
+
+        return format!(
+            "{{\n  \"locations\": [\n{}\n  ],\n  \"clusters\": [\n{}\n  ],\n  \"edges\": [\n{}\n  ]\n}}\n",
+            locations.join(",\n"),
+            clusters.join(",\n"),
+            edges.join(",\n")
+        );
+    }
+}
 
-    let mut clusters = pre_add_locations(locations);
+fn write_to_file(path: &str, text: &str) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
 
-    println!("Starting clustering");
+fn part1(contents: &String, num_connections: usize) -> Option<Answer> {
+    let locations = parse_locations(contents);
+    let tree = minimum_spanning_tree(&locations);
 
-    println!("Finding required connections");
-    let mut i = 0;
-    let mut pair= None;
-    while clusters.len() != 1  {
-        pair = Some(&closest_pairs[i]);
-        println!("\nFinding cluster for {pair:?}");
-        clusters = add_pairs_to_clusters(clusters, pair.expect("Expected a pair here"));
-        i += 1;
-    }
+    let circuits = tree.clusters_after(num_connections).components();
+    println!("There are {circuits} circuits after {num_connections} connections");
 
-    println!("Final pair was {pair:?} i was {i}");
+    let sizes = tree.top_cluster_sizes(num_connections, 3);
+    dbg!(&sizes);
 
-    if pair.is_none() {
-        panic!("Pair was not initialized");
-    }
+    let answer = sizes.iter().map(|size| *size as u64).product();
 
-    let answer = (pair.expect("Expected a pair").loc1.x * pair.expect("Expected a pair").loc2.x) as u64;
-    return Some(Answer{answer})
+    Some(Answer { answer })
 }
 
-fn pre_add_locations(locations: Vec<Location>) -> Vec<LocationCluster> {
-    let mut clusters = vec![];
+// Part 1 attempted answers
 
-    for location in locations {
-        clusters.push(LocationCluster{locations: vec![location], pairs: vec![]})
-    }
+fn part2(contents: &String) -> Option<Answer> {
+    let locations = parse_locations(contents);
+    let tree = minimum_spanning_tree(&locations);
+    println!(
+        "Spanning tree took {} merges, total length {:.3}",
+        tree.dendrogram.len(),
+        tree.total_weight()
+    );
 
-    return clusters;
+    let edge = tree
+        .last_connecting_edge()
+        .expect("Expected every location to end up connected");
+    println!(
+        "Final pair was {:?} after {} connections",
+        edge.pair,
+        edge.connection + 1
+    );
 
+    let answer = (edge.pair.loc1.x() * edge.pair.loc2.x()) as u64;
+    return Some(Answer { answer });
 }
 
 // Part 2 attempted answers
@@ -355,6 +639,7 @@ fn main() {
     let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
     let do_part1 = false;
     let do_part2 = true;
+    let do_export = false;
     if do_part1 {
         let result1 = part1(&contents, 1000);
         println!("Part1 result {result1:?}");
@@ -364,6 +649,14 @@ fn main() {
         let result2 = part2(&contents);
         println!("Part2 result {result2:?}");
     }
+
+    if do_export {
+        let locations = parse_locations(&contents);
+        let tree = minimum_spanning_tree(&locations);
+        let export = tree.export(&locations, 1000);
+        write_to_file("clusters.dot", &export.to_dot()).expect("Expected to write the DOT file");
+        write_to_file("clusters.json", &export.to_json()).expect("Expected to write the JSON file");
+    }
 }
 
 // Tests
@@ -432,6 +725,195 @@ mod tests {
         assert_eq!(result, Some(Answer { answer: 40 }));
     }
 
+    #[test]
+    fn test_disjoint_set() {
+        let mut set = DisjointSet::new(6);
+        assert_eq!(set.components(), 6);
+
+        assert!(set.union(0, 1));
+        assert!(set.union(2, 3));
+        assert!(set.union(1, 3));
+        assert!(!set.union(0, 2));
+        assert_eq!(set.components(), 3);
+        assert_eq!(set.component_sizes(), vec![4, 1, 1]);
+        assert_eq!(set.final_merge, None);
+
+        set.union(4, 5);
+        set.union(5, 0);
+        assert_eq!(set.components(), 1);
+        assert_eq!(set.final_merge, Some((5, 0)));
+        assert_eq!(set.find(4), set.find(1));
+    }
+
+    #[test]
+    fn test_pair_ordering_large_coordinates() {
+        let origin = Point::origin();
+        let pair = |index: usize, x: i64| LocationPair {
+            loc1: origin,
+            loc2: Point::new([x, 0, 0]),
+            index1: 0,
+            index2: index,
+        };
+
+        // The squared distances are around 10^16 and only differ by 200_000_001,
+        // which is smaller than the gap between neighbouring f32 values there
+        let near = pair(1, 100_000_000);
+        let far = pair(2, 100_000_001);
+        assert_eq!(
+            far.squared_distance() - near.squared_distance(),
+            200_000_001
+        );
+        assert!(near < far);
+
+        // Pairs the same distance apart fall back to the indexes
+        let swapped = LocationPair {
+            loc1: Point::new([5, 0, 0]),
+            loc2: origin,
+            index1: 2,
+            index2: 0,
+        };
+        let mut pairs = [pair(3, 5), pair(2, 5), pair(1, 5), swapped];
+        pairs.sort();
+        let indexes: Vec<(usize, usize)> = pairs.iter().map(|p| (p.index1, p.index2)).collect();
+        assert_eq!(indexes, vec![(0, 1), (0, 2), (2, 0), (0, 3)]);
+    }
+
+    #[test]
+    fn test_closest_pairs_matches_brute_force() {
+        // Small coordinates so there are plenty of ties and repeated locations
+        let mut state: u64 = 12345;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) % 7) as i64
+        };
+        let locations: Vec<Point<3>> = (0..60)
+            .map(|_| Point::new([next(), next(), next()]))
+            .collect();
+
+        let mut expected: Vec<LocationPair> = vec![];
+        for index1 in 0..locations.len() {
+            for index2 in index1 + 1..locations.len() {
+                expected.push(LocationPair {
+                    loc1: locations[index1],
+                    loc2: locations[index2],
+                    index1,
+                    index2,
+                });
+            }
+        }
+        expected.sort();
+
+        let streamed: Vec<LocationPair> = ClosestPairs::new(&locations).collect();
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_minimum_spanning_tree() {
+        // Two pairs of close locations, far apart from each other
+        let contents = "0,0,0\n3,0,0\n100,0,0\n104,0,0\n1,0,0".to_string();
+        let locations = parse_locations(&contents);
+        let tree = minimum_spanning_tree(&locations);
+
+        let edges: Vec<(usize, usize, usize)> = tree
+            .edges
+            .iter()
+            .map(|edge| (edge.pair.index1, edge.pair.index2, edge.connection))
+            .collect();
+        // The 0-1 pair (length 3) is skipped, as 0 and 1 are already joined through 4
+        assert_eq!(edges, vec![(0, 4, 0), (1, 4, 1), (2, 3, 3), (1, 2, 4)]);
+        assert_eq!(tree.total_weight(), 1.0 + 2.0 + 4.0 + 97.0);
+
+        assert_eq!(
+            tree.dendrogram,
+            vec![
+                MergeEvent {
+                    left: 0,
+                    right: 4,
+                    merged: 5,
+                    size: 2
+                },
+                MergeEvent {
+                    left: 1,
+                    right: 5,
+                    merged: 6,
+                    size: 3
+                },
+                MergeEvent {
+                    left: 2,
+                    right: 3,
+                    merged: 7,
+                    size: 2
+                },
+                MergeEvent {
+                    left: 6,
+                    right: 7,
+                    merged: 8,
+                    size: 5
+                },
+            ]
+        );
+
+        assert_eq!(tree.top_cluster_sizes(0, 2), vec![1, 1]);
+        assert_eq!(tree.top_cluster_sizes(3, 3), vec![3, 1, 1]);
+        assert_eq!(tree.top_cluster_sizes(4, 3), vec![3, 2]);
+        assert_eq!(tree.clusters_after(5).components(), 1);
+
+        let last = tree.last_connecting_edge().expect("Expected a last edge");
+        assert_eq!((last.pair.index1, last.pair.index2), (1, 2));
+    }
+
+    #[test]
+    fn test_cluster_export() {
+        let contents = "0,0,0\n3,0,0\n100,0,0\n104,0,0\n1,0,0".to_string();
+        let locations = parse_locations(&contents);
+        let tree = minimum_spanning_tree(&locations);
+        let export = tree.export(&locations, 4);
+
+        // 0, 1 and 4 make the largest cluster, then 2 and 3
+        assert_eq!(export.cluster_ids, vec![0, 0, 1, 1, 0]);
+        assert_eq!(export.clusters, vec![vec![0, 1, 4], vec![2, 3]]);
+
+        let dot = export.to_dot();
+        assert!(dot.starts_with("graph junction_boxes {\n"));
+        assert!(dot.contains(
+            "    subgraph cluster_1 {\n        label=\"cluster 1, size 2\";\n        n2 [label=\"100,0,0\", fillcolor=\"#3cb44b\"];\n        n3 [label=\"104,0,0\", fillcolor=\"#3cb44b\"];\n    }\n"
+        ));
+        assert!(!dot.contains("cluster="));
+        assert!(dot.contains("    n0 -- n4 [label=\"1.000\", color=\"#e6194b\"];\n"));
+        assert!(!dot.contains("n1 -- n2"));
+
+        assert_eq!(
+            export.to_json(),
+            r#"{
+  "locations": [
+    {"index": 0, "x": 0, "y": 0, "z": 0, "cluster": 0},
+    {"index": 1, "x": 3, "y": 0, "z": 0, "cluster": 0},
+    {"index": 2, "x": 100, "y": 0, "z": 0, "cluster": 1},
+    {"index": 3, "x": 104, "y": 0, "z": 0, "cluster": 1},
+    {"index": 4, "x": 1, "y": 0, "z": 0, "cluster": 0}
+  ],
+  "clusters": [
+    {"id": 0, "size": 3, "locations": [0, 1, 4]},
+    {"id": 1, "size": 2, "locations": [2, 3]}
+  ],
+  "edges": [
+    {"from": 0, "to": 4, "connection": 0, "squared_distance": 1, "distance": 1, "cluster": 0},
+    {"from": 1, "to": 4, "connection": 1, "squared_distance": 4, "distance": 2, "cluster": 0},
+    {"from": 2, "to": 3, "connection": 3, "squared_distance": 16, "distance": 4, "cluster": 1}
+  ]
+}
+"#
+        );
+
+        // Locations on their own are greyed out, and not in a cluster box
+        let export = tree.export(&locations, 1);
+        let dot = export.to_dot();
+        assert!(dot.contains("\n    n2 [label=\"100,0,0\", fillcolor=\"#a9a9a9\"];\n"));
+        assert_eq!(dot.matches("subgraph").count(), 1);
+    }
+
     #[test]
     fn test_part1() {
         let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
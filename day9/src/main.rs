@@ -1,8 +1,6 @@
-#![allow(unused_variables, dead_code)]
-
-use macroquad::prelude::*;
-use std::{cmp::Ordering, fs};
-
+use geometry::{Point, Rect, Segment};
+use scene::{Colour, Item, Position, Scene, Shape, Style, svg, viewer};
+use std::{cmp::Ordering, fmt, fs, fs::File, io::Write};
 
 #[derive(Debug, PartialEq)]
 struct Answer {
@@ -23,55 +21,251 @@ impl InputGetter for LocalFileInputGetter {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash)]
-struct TwoDimensionalLocation {
-    x: i64,
-    y: i64,
-}
-
 #[derive(Debug, Eq, PartialEq, Clone)]
 struct TwoDimensionalLocationPair {
-    loc1: TwoDimensionalLocation,
-    loc2: TwoDimensionalLocation,
+    loc1: Point<2>,
+    loc2: Point<2>,
 }
 
 impl TwoDimensionalLocationPair {
-    fn calculate_square_size(&self) -> i64 {
-        let x_diff: i64 = self.loc1.x - (self.loc2.x + 1).abs();
-        let y_diff: i64 = self.loc1.y - (self.loc2.y + 1).abs();
-        return x_diff * y_diff;
+    fn bounding_box(&self) -> Rect<2> {
+        return Rect::from_corners(self.loc1, self.loc2);
+    }
+
+    // Number of tiles covered, counting the tiles the corners are on
+    fn inclusive_area(&self) -> u128 {
+        let bounding_box = self.bounding_box();
+        return bounding_box.extent(0) * bounding_box.extent(1);
     }
 }
 
 impl PartialOrd for TwoDimensionalLocationPair {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        (self.calculate_square_size()).partial_cmp(&other.calculate_square_size())
+        Some(self.cmp(other))
     }
 }
 
+// Ordered by area, with the corners only used to break ties
 impl Ord for TwoDimensionalLocationPair {
     fn cmp(&self, other: &Self) -> Ordering {
-        (self.calculate_square_size())
-            .partial_cmp(&other.calculate_square_size())
-            .expect("Expected an Ordering here")
+        self.inclusive_area()
+            .cmp(&other.inclusive_area())
+            .then_with(|| (self.loc1, self.loc2).cmp(&(other.loc1, other.loc2)))
     }
 }
 
-fn parse_locations(contents: &String) -> Vec<TwoDimensionalLocation> {
-    let mut locations: Vec<TwoDimensionalLocation> = vec![];
+fn parse_locations(contents: &String) -> Vec<Point<2>> {
+    let mut locations: Vec<Point<2>> = vec![];
 
     for line in contents.lines() {
-        let split: Vec<&str> = line.split(",").collect();
-        locations.push(TwoDimensionalLocation {
-            x: split[0].parse().expect("Expected a value here"),
-            y: split[1].parse().expect("Expected a value here"),
-        })
+        locations.push(line.parse().expect("Expected a location here"))
     }
 
     return locations;
 }
 
-fn create_pairs(locations: &Vec<TwoDimensionalLocation>) -> Vec<TwoDimensionalLocationPair> {
+#[derive(Debug, PartialEq)]
+enum PolygonError {
+    TooFewVertices {
+        count: usize,
+    },
+    RepeatedVertex {
+        index: usize,
+        location: Point<2>,
+    },
+    DiagonalEdge {
+        from: Point<2>,
+        to: Point<2>,
+    },
+    NotClosed {
+        first: Point<2>,
+        last: Point<2>,
+    },
+    SelfIntersection {
+        edge1: Segment<2>,
+        edge2: Segment<2>,
+    },
+}
+
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolygonError::TooFewVertices { count } => {
+                write!(
+                    f,
+                    "a loop needs at least 4 red tiles, but there were {count}"
+                )
+            }
+            PolygonError::RepeatedVertex { index, location } => {
+                write!(
+                    f,
+                    "red tile {index} at {location} is the same as the one before it"
+                )
+            }
+            PolygonError::DiagonalEdge { from, to } => {
+                write!(
+                    f,
+                    "red tiles {from} and {to} are not in the same row or column"
+                )
+            }
+            PolygonError::NotClosed { first, last } => {
+                write!(
+                    f,
+                    "the loop can't be closed, last red tile {last} is not in the same row or column as first red tile {first}"
+                )
+            }
+            PolygonError::SelfIntersection { edge1, edge2 } => write!(
+                f,
+                "the line from {} to {} runs into the line from {} to {}",
+                edge1.start, edge1.end, edge2.start, edge2.end
+            ),
+        }
+    }
+}
+
+// Orientation in the usual maths sense, with y going up. The puzzle's y goes
+// down the screen, so these look the other way round when drawn.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Orientation {
+    Clockwise,
+    Anticlockwise,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum VertexKind {
+    // Turns towards the inside of the loop
+    Convex,
+    // Turns away from it, making a notch
+    Reflex,
+    // Doesn't turn at all
+    Straight,
+}
+
+// The loop of red tiles, checked to only use straight lines along rows and
+// columns that never cross or touch each other
+#[derive(Debug, Clone)]
+struct RectilinearPolygon {
+    vertices: Vec<Point<2>>,
+}
+
+impl RectilinearPolygon {
+    fn new(vertices: Vec<Point<2>>) -> Result<Self, PolygonError> {
+        if vertices.len() < 4 {
+            return Err(PolygonError::TooFewVertices {
+                count: vertices.len(),
+            });
+        }
+
+        let polygon = RectilinearPolygon { vertices };
+        let edges: Vec<Segment<2>> = polygon.edges().collect();
+        let last_index = edges.len() - 1;
+
+        // The first edge is the one that closes the loop
+        for (index, edge) in edges.iter().enumerate() {
+            if edge.start == edge.end {
+                return Err(PolygonError::RepeatedVertex {
+                    index,
+                    location: edge.end,
+                });
+            }
+            if edge.axis().is_none() {
+                if index == 0 {
+                    return Err(PolygonError::NotClosed {
+                        first: edge.end,
+                        last: edge.start,
+                    });
+                }
+                return Err(PolygonError::DiagonalEdge {
+                    from: edge.start,
+                    to: edge.end,
+                });
+            }
+        }
+
+        for (index1, edge1) in edges.iter().enumerate() {
+            for (index2, edge2) in edges.iter().enumerate().skip(index1 + 1) {
+                let adjacent = index2 == index1 + 1 || (index1 == 0 && index2 == last_index);
+                let crosses = if adjacent {
+                    // Next to each other they always share a corner, so only
+                    // doubling back along the same line counts
+                    edge1.axis() == edge2.axis() && {
+                        let (shared, other1, other2) = if edge1.end == edge2.start {
+                            (edge1.end, edge1.start, edge2.end)
+                        } else {
+                            (edge1.start, edge1.end, edge2.start)
+                        };
+                        let axis = edge1.axis().expect("Expected edges to be checked already");
+                        (other1.coords[axis] - shared.coords[axis]).signum()
+                            == (other2.coords[axis] - shared.coords[axis]).signum()
+                    }
+                } else {
+                    edge1.bounding_box().intersects(&edge2.bounding_box())
+                };
+
+                if crosses {
+                    return Err(PolygonError::SelfIntersection {
+                        edge1: *edge1,
+                        edge2: *edge2,
+                    });
+                }
+            }
+        }
+
+        return Ok(polygon);
+    }
+
+    // Each red tile to the next, finishing with the last one back to the first
+    fn edges(&self) -> impl Iterator<Item = Segment<2>> + '_ {
+        let previous = self.vertices.iter().cycle().skip(self.vertices.len() - 1);
+        return previous
+            .zip(&self.vertices)
+            .map(|(from, to)| Segment::new(*from, *to));
+    }
+
+    // Shoelace formula, doubled so that it stays a whole number
+    fn twice_signed_area(&self) -> i128 {
+        return self
+            .edges()
+            .map(|edge| {
+                edge.start.x() as i128 * edge.end.y() as i128
+                    - edge.end.x() as i128 * edge.start.y() as i128
+            })
+            .sum();
+    }
+
+    fn orientation(&self) -> Orientation {
+        if self.twice_signed_area() > 0 {
+            return Orientation::Anticlockwise;
+        }
+        return Orientation::Clockwise;
+    }
+
+    fn vertex_kinds(&self) -> Vec<VertexKind> {
+        let orientation = self.orientation();
+        let edges: Vec<Segment<2>> = self.edges().collect();
+        let mut kinds = vec![];
+
+        for (index, incoming) in edges.iter().enumerate() {
+            let outgoing = &edges[(index + 1) % edges.len()];
+            let (before, after) = (incoming.end - incoming.start, outgoing.end - outgoing.start);
+            let cross =
+                before.x() as i128 * after.y() as i128 - before.y() as i128 * after.x() as i128;
+
+            let kind = match (cross.signum(), orientation) {
+                (0, _) => VertexKind::Straight,
+                (1, Orientation::Anticlockwise) | (-1, Orientation::Clockwise) => {
+                    VertexKind::Convex
+                }
+                _ => VertexKind::Reflex,
+            };
+            kinds.push(kind);
+        }
+
+        return kinds;
+    }
+}
+
+fn create_pairs(locations: &Vec<Point<2>>) -> Vec<TwoDimensionalLocationPair> {
     let mut out_pairs: Vec<TwoDimensionalLocationPair> = vec![];
     for location in locations {
         for other_location in locations {
@@ -80,8 +274,8 @@ fn create_pairs(locations: &Vec<TwoDimensionalLocation>) -> Vec<TwoDimensionalLo
             }
 
             out_pairs.push(TwoDimensionalLocationPair {
-                loc1: location.clone(),
-                loc2: other_location.clone(),
+                loc1: *location,
+                loc2: *other_location,
             })
         }
     }
@@ -90,299 +284,206 @@ fn create_pairs(locations: &Vec<TwoDimensionalLocation>) -> Vec<TwoDimensionalLo
 }
 
 fn part1(contents: &String) -> Option<Answer> {
-    let locations: Vec<TwoDimensionalLocation> = parse_locations(contents);
+    let locations: Vec<Point<2>> = parse_locations(contents);
 
     let mut pairs: Vec<TwoDimensionalLocationPair> = create_pairs(&locations);
 
     pairs.sort();
     pairs.reverse();
 
-    let answer = pairs[0].calculate_square_size() as u64;
+    let answer =
+        u64::try_from(pairs[0].inclusive_area()).expect("Expected the area to fit in a u64");
 
     return Some(Answer { answer });
 }
 
 // Part 1 attempted answers
 
-fn part2(contents: &String) -> (Option<Answer>, Vec<TwoDimensionalLocation>, Vec<TwoDimensionalLocationPair>) {
+fn part2(
+    contents: &String,
+) -> (
+    Option<Answer>,
+    RectilinearPolygon,
+    Vec<TwoDimensionalLocationPair>,
+) {
     println!("Parsing locations...");
     // Generate all the pairs, same as part1
-    let locations: Vec<TwoDimensionalLocation> = parse_locations(contents);
-
-    println!("Creating pairs...");
-    // let mut pairs: Vec<TwoDimensionalLocationPair> = create_part2_pairs(locations);
-    let mut pairs: Vec<TwoDimensionalLocationPair> = create_pairs(&locations);
-
-    let total_pairs = pairs.len();
-    println!("There were {total_pairs} total pairs");
-
-    pairs = filter_pairs(&locations, pairs);
-
-    // Output largest valid square
-
-    pairs.sort();
-    pairs.reverse();
-
-    let num_pairs = pairs.len();
-
-    println!("There were {num_pairs} valid pairs");
-
-    for pair in &pairs[..9] {
-        let size = pair.calculate_square_size();
+    let locations: Vec<Point<2>> = parse_locations(contents);
+    let polygon = RectilinearPolygon::new(locations.clone())
+        .unwrap_or_else(|error| panic!("Expected the red tiles to form a loop, but {error}"));
+    let reflex_corners = polygon
+        .vertex_kinds()
+        .iter()
+        .filter(|kind| **kind == VertexKind::Reflex)
+        .count();
+    println!(
+        "The loop goes {:?} with {reflex_corners} reflex corners",
+        polygon.orientation()
+    );
+
+    let pairs = best_pairs(&polygon, TOP_PAIRS);
+    for pair in &pairs {
+        let size = pair.inclusive_area();
         println!("For pair {pair:?} size is {size}")
     }
 
-    let out_pair = &pairs[0];
+    let out_pair = pairs.first().expect("Expected a rectangle inside the loop");
     dbg!(&out_pair);
-    let answer = out_pair.calculate_square_size() as u64;
+    let answer =
+        u64::try_from(out_pair.inclusive_area()).expect("Expected the area to fit in a u64");
 
-    return (Some(Answer { answer }), locations, pairs);
+    return (Some(Answer { answer }), polygon, pairs);
 }
 
-fn filter_pairs(
-    locations: &Vec<TwoDimensionalLocation>,
-    pairs: Vec<TwoDimensionalLocationPair>,
-) -> Vec<TwoDimensionalLocationPair> {
-    let mut out_pairs = vec![];
-
-
-    // Pre-compute what the vertical, and what the horizontal lines are
-
-    let (vertical_lines, horizontal_lines) = sort_lines(locations);
-
-    for pair in pairs {
-        // let pair_size = pair.calculate_square_size();
-        // println!("Checking pair {pair:?} with size {pair_size}");
-
-        if check_valid_pair(&pair, locations, &vertical_lines, &horizontal_lines) {
-            out_pairs.push(pair);
-        }
-    }
-    return out_pairs;
-}
-
-fn sort_lines(locations: &[TwoDimensionalLocation]) -> (Vec<TwoDimensionalLocationPair>, Vec<TwoDimensionalLocationPair>) {
-    let mut vertical_lines = vec![];
-    let mut horizontal_lines = vec![];
-    let mut loc1 = &locations[locations.len()-1];
-
+// Number of the largest rectangles kept for drawing
+const TOP_PAIRS: usize = 10;
+
+// The largest different rectangles that fit inside the loop, best first.
+// Each pair of red tiles is looked at once without storing them all, and only
+// checked against the grid if it is big enough to make the list.
+fn best_pairs(polygon: &RectilinearPolygon, count: usize) -> Vec<TwoDimensionalLocationPair> {
+    // Pre-compute which parts of the floor are red or green
+    let grid = CompressedGrid::new(polygon);
+    let locations = &polygon.vertices;
+    let mut best: Vec<TwoDimensionalLocationPair> = vec![];
+
+    for (index, location) in locations.iter().enumerate() {
+        for other_location in &locations[index + 1..] {
+            // The bigger corner first, so each rectangle is always given the
+            // same way round
+            let pair = TwoDimensionalLocationPair {
+                loc1: *location.max(other_location),
+                loc2: *location.min(other_location),
+            };
+            if best.len() == count && best.last().is_some_and(|worst| pair <= *worst) {
+                continue;
+            }
 
-    for loc2 in locations {
+            let rect = pair.bounding_box();
+            // The other two corners can be red tiles too, in which case the
+            // bigger pair is kept
+            if let Some(same) = best.iter().position(|other| other.bounding_box() == rect) {
+                if pair > best[same] {
+                    best[same] = pair;
+                    best.sort_by(|pair1, pair2| pair2.cmp(pair1));
+                }
+                continue;
+            }
 
-        if loc1.x != loc2.x {
-            // Horizontal line
-            horizontal_lines.push(TwoDimensionalLocationPair { loc1: loc1.clone(), loc2: loc2.clone() })
-        } else {
-            // Vertical line
-            vertical_lines.push(TwoDimensionalLocationPair { loc1: loc1.clone(), loc2: loc2.clone() })
+            if !grid.contains_rect(&rect) {
+                continue;
+            }
+            let position = best.partition_point(|other| *other > pair);
+            best.insert(position, pair);
+            best.truncate(count);
         }
-
-        loc1 = loc2;        
     }
 
-    return (vertical_lines, horizontal_lines)
-    
+    return best;
 }
 
-fn check_valid_pair(
-    pair: &TwoDimensionalLocationPair,
-    locations: &[TwoDimensionalLocation],
-    vertical_lines: &Vec<TwoDimensionalLocationPair>,
-    horizontal_lines: &Vec<TwoDimensionalLocationPair>
-) -> bool {
-    for location in locations {
-        if location == &pair.loc1 || location == &pair.loc2 {
-            continue;
-        }
-
-        if location_inside_pair(location, pair) {
-            // println!("{pair:?} is invalid due to {location:?}");
-            return false;
-        }
-    }
-
-    // We've checked no locations inside, but they could go all the way
-    // across. So we need to check for these "overlaps"
-
-    // We do this by looping through all the locations and checking if
-    // they cross over any of the lines that make the pair
-
-    // let mut prev_loc = &locations[locations.len() - 1];
-    // for location in locations {
-    //     if crosses_line(pair, location, prev_loc) {
-    //         return false;
-    //     }
-
-    //     prev_loc = location;
-    // }
-    if check_crosses_line(pair, vertical_lines, horizontal_lines) {
-        return false
-    }
-
-    return true;
+// The tiles squashed down into blocks, with a new column starting at each x
+// used by a red tile and just after it, and the same for rows. Nothing on
+// the loop changes partway through a block, so each block is either all red
+// or green, or none of it is.
+struct CompressedGrid {
+    starts: [Vec<i64>; 2],
+    // Number of filled blocks above and to the left of each block
+    prefix_sums: Vec<u64>,
 }
 
-fn check_crosses_line(pair: &TwoDimensionalLocationPair, vertical_lines: &[TwoDimensionalLocationPair], horizontal_lines: &[TwoDimensionalLocationPair]) -> bool {
-
-    // Check horizontal lines    
-
-    // for vertical_line in vertical_lines {
-    //     // First horizontal line in pair
-
-    //     // Second horizontal line in pair
-
-    // }
-    for horizontal_line in horizontal_lines {
-        let (low_y, high_y) = sort_values(pair.loc1.y, pair.loc2.y);
-        let (low_line_x, high_line_x) = sort_values(horizontal_line.loc1.x, horizontal_line.loc2.x);
-        let (low_pair_x, high_pair_x) = sort_values(pair.loc1.x, pair.loc2.x);
-        // First check if the y of this horizontal line, is within the y of the pair
-        if horizontal_line.loc1.y > low_y && horizontal_line.loc1.y < high_y {
-            // Now check, if the two sides of the line are *outside* the values of the pair, then this is an invalid square
+impl CompressedGrid {
+    fn new(polygon: &RectilinearPolygon) -> Self {
+        let starts: [Vec<i64>; 2] = std::array::from_fn(|axis| {
+            let mut axis_starts: Vec<i64> = polygon
+                .vertices
+                .iter()
+                .flat_map(|location| [location.coords[axis], location.coords[axis] + 1])
+                .collect();
+            axis_starts.sort();
+            axis_starts.dedup();
+            axis_starts
+        });
+        let width = starts[0].len();
+        let height = starts[1].len();
+        let block =
+            |axis: usize, value: i64| starts[axis].partition_point(|start| *start <= value) - 1;
+
+        let mut filled = vec![false; width * height];
+        let mut toggles = vec![false; width * height];
+        for edge in polygon.edges() {
+            let bounds = edge.bounding_box();
+            let (min_col, max_col) = (block(0, bounds.min.x()), block(0, bounds.max.x()));
+            let (min_row, max_row) = (block(1, bounds.min.y()), block(1, bounds.max.y()));
+
+            // The loop itself
+            for row in min_row..=max_row {
+                for col in min_col..=max_col {
+                    filled[row * width + col] = true;
+                }
+            }
 
-            if low_line_x <= low_pair_x && high_line_x >= high_pair_x {
-                return true
+            // Vertical lines flip between inside and outside, counting the
+            // bottom end of the line but not the top one
+            if edge.axis() == Some(1) {
+                for row in min_row..max_row {
+                    toggles[row * width + min_col] ^= true;
+                }
             }
         }
-    }
-
-    return false
-}
-
-fn crosses_line(
-    pair: &TwoDimensionalLocationPair,
-    tile_line_loc1: &TwoDimensionalLocation,
-    tile_line_loc2: &TwoDimensionalLocation,
-) -> bool {
-    let pair_loc1 = &pair.loc1;
-    let pair_loc2 = &pair.loc2;
-
-
-    if tile_line_loc1.y == tile_line_loc2.y {
-        // Horizontal tile line, vertical pair lines
-
-        let (low_tile_x, high_tile_x) = sort_values(tile_line_loc1.x, tile_line_loc2.x);
-        let (low_pair_y, high_pair_y) = sort_values(pair_loc1.y, pair_loc2.y);
 
-        // Doing pair_loc1
-        if pair_loc1.x >= low_tile_x && pair_loc1.x <= high_tile_x {
-            if tile_line_loc1.y > low_pair_y && tile_line_loc1.y < high_pair_y {
-                return true
+        // A block is inside when there are an odd number of lines to its right
+        for row in 0..height {
+            let mut inside = false;
+            for col in (0..width).rev() {
+                filled[row * width + col] |= inside;
+                inside ^= toggles[row * width + col];
             }
         }
-        // Doing pair_loc2
-        if pair_loc2.x > low_tile_x && pair_loc2.x < high_tile_x {
-            if tile_line_loc2.y > low_pair_y && tile_line_loc2.y < high_pair_y {
-                return true
+
+        let mut prefix_sums = vec![0; (width + 1) * (height + 1)];
+        for row in 0..height {
+            for col in 0..width {
+                prefix_sums[(row + 1) * (width + 1) + col + 1] = filled[row * width + col] as u64
+                    + prefix_sums[row * (width + 1) + col + 1]
+                    + prefix_sums[(row + 1) * (width + 1) + col]
+                    - prefix_sums[row * (width + 1) + col];
             }
         }
-    }
-
-    return false;
-}
-
-
-fn location_inside_pair(
-    location: &TwoDimensionalLocation,
-    pair: &TwoDimensionalLocationPair,
-) -> bool {
-    let (low_x, high_x) = sort_values(pair.loc1.x, pair.loc2.x);
-    let (low_y, high_y) = sort_values(pair.loc1.y, pair.loc2.y);
-    if location.x - 1 < low_x || location.x + 1 > high_x {
-        return false;
-    }
 
-    if location.y - 1 < low_y || location.y + 1 > high_y {
-        return false;
+        return CompressedGrid {
+            starts,
+            prefix_sums,
+        };
     }
 
-    return true;
-}
-
-fn sort_values(v1: i64, v2: i64) -> (i64, i64) {
-    if v1 > v2 {
-        return (v2, v1);
+    // Which block along the axis a value is in, if it's not before all of them
+    fn block(&self, axis: usize, value: i64) -> Option<usize> {
+        return self.starts[axis]
+            .partition_point(|start| *start <= value)
+            .checked_sub(1);
     }
-    return (v1, v2);
-}
 
-#[allow(dead_code)]
-fn create_part2_pairs(
-    mut locations: Vec<TwoDimensionalLocation>,
-) -> Vec<TwoDimensionalLocationPair> {
-    // Instead of pairs we look at the triples that are next to each other
-    // And only every other triple is valid.
-
-    let mut pairs: Vec<TwoDimensionalLocationPair> = vec![];
-
-    locations.reverse();
-
-    let len_locations = locations.len();
-    let mut location1 = locations
-        .get(len_locations - 2)
-        .expect("Expected location 1");
-    let mut location2 = locations
-        .get(len_locations - 1)
-        .expect("Expected location 2");
-
-    let mut val = 1;
-
-    for location3 in &locations {
-        // Skip every other one
-        val += 1;
-        let pair = TwoDimensionalLocationPair {
-            loc1: location1.clone(),
-            loc2: location3.clone(),
+    fn contains_rect(&self, rect: &Rect<2>) -> bool {
+        let (Some(min_col), Some(min_row)) =
+            (self.block(0, rect.min.x()), self.block(1, rect.min.y()))
+        else {
+            return false;
         };
-        if is_left_turn(location1, location2, location3) {
-            let size = pair.calculate_square_size();
-            println!("Adding pair {pair:?} with size {size}");
-            pairs.push(pair);
-        } else {
-            println!("Skipping {val} {pair:?}");
-        }
-
-        location1 = location2;
-        location2 = location3;
-        println!("val is {val}");
+        let max_col = self
+            .block(0, rect.max.x())
+            .expect("Expected max to be after min");
+        let max_row = self
+            .block(1, rect.max.y())
+            .expect("Expected max to be after min");
+
+        let stride = self.starts[0].len() + 1;
+        let filled = self.prefix_sums[(max_row + 1) * stride + max_col + 1]
+            + self.prefix_sums[min_row * stride + min_col]
+            - self.prefix_sums[min_row * stride + max_col + 1]
+            - self.prefix_sums[(max_row + 1) * stride + min_col];
+        return filled == ((max_col - min_col + 1) * (max_row - min_row + 1)) as u64;
     }
-    return pairs;
-}
-
-fn is_left_turn(
-    location1: &TwoDimensionalLocation,
-    location2: &TwoDimensionalLocation,
-    location3: &TwoDimensionalLocation,
-) -> bool {
-    // Its a left turn if the following:
-    // Left
-    // -diff1_x       -diff2_y
-    // +diff1_x       +diff2_y
-    // -diff1_y       -diff2_x
-    // +diff1_y       -diff2_x
-
-    let diff1_x = location1.x - location2.x;
-    // let diff1_y = location1.y - location2.y;
-
-    let diff2_x = location2.x - location3.x;
-    let diff2_y = location2.y - location3.y;
-
-    if diff1_x == 0 {
-        // For both -diff1_y and +diff1_y they are left if -diff2_x
-        if diff2_x < 0 {
-            return true;
-        }
-    } else {
-        // -x or +x
-        if diff1_x < 0 && diff2_y < 0 {
-            return true;
-        } else if diff1_x > 0 && diff2_y > 0 {
-            return true;
-        }
-    }
-
-    // Otherwise it is a right turn
-    return false;
 }
 
 // Part 2 attempted answers
@@ -394,93 +495,130 @@ fn is_left_turn(
 // 6 1276381001
 // 7 1289195182
 
-async fn draw_map(locations: &Vec<TwoDimensionalLocation>, pairs: &Vec<TwoDimensionalLocationPair>) {
-    loop {
-        clear_background(WHITE);
-        let mut prev_location = &locations[locations.len() - 1];
-        let mut min_x = f32::MAX;
-        let mut min_y = f32::MAX;
-        let mut max_x = 0.0;
-        let mut max_y = 0.0;
-
-        let thickness_multiplier = 100.0;
-        // let thickness_multiplier = 0.01;
-
-        for location in locations {
-            if (location.x as f32) < min_x {
-                min_x = location.x as f32;
-            }
-            if (location.y as f32) < min_y {
-                min_y = location.x as f32;
-            }
-            if (location.x as f32) > max_x {
-                max_x = location.x as f32;
-            }
-            if (location.y as f32) > max_y {
-                max_y = location.y as f32;
-            }
-            draw_line(
-                prev_location.x as f32,
-                prev_location.y as f32,
-                location.x as f32,
-                location.y as f32,
-                3.0*thickness_multiplier,
-                BLUE,
-            );
-            prev_location = location;
-        }
-
-            let out_pair = &pairs[0];
-            let width = (out_pair.loc2.x - out_pair.loc1.x) as f32;
-            let height = (out_pair.loc2.y - out_pair.loc1.y) as f32;
-            draw_rectangle(
-                out_pair.loc1.x as f32,
-                out_pair.loc1.y as f32,
-                width,
-                height,
-                RED,
-            );
-            let colours = vec![GREEN, BLUE, BLACK, RED, ORANGE, PURPLE, YELLOW, GRAY, LIGHTGRAY, LIME];
-
+const CANDIDATE_COLOURS: [Colour; 8] = [
+    Colour::GREEN,
+    Colour::BLUE,
+    Colour::BLACK,
+    Colour::ORANGE,
+    Colour::PURPLE,
+    Colour::YELLOW,
+    Colour::GRAY,
+    Colour::LIME,
+];
+
+// Tiles are a whole unit wide, centred on their location
+fn tile_corners(rect: &Rect<2>) -> (Position, Position) {
+    return (
+        Position::new(rect.min.x() as f64 - 0.5, rect.min.y() as f64 - 0.5),
+        Position::new(rect.max.x() as f64 + 0.5, rect.max.y() as f64 + 0.5),
+    );
+}
 
-            for (i, pair) in pairs[..9].iter().enumerate() {
-                draw_circle(pair.loc1.x as f32, pair.loc1.y as f32, 9.0*thickness_multiplier, colours[i]);
-                draw_circle(pair.loc2.x as f32, pair.loc2.y as f32, 9.0*thickness_multiplier, colours[i]);
-            }
+fn tile_centre(location: &Point<2>) -> Position {
+    return Position::new(location.x() as f64, location.y() as f64);
+}
 
-        // draw_line(40.0, 40.0, 100.0, 200.0, 1.0, BLUE);
-        let camera = fit_camera_to_bounds(min_x, min_y, max_x, max_y);
+fn tile_rect(rect: &Rect<2>) -> Shape {
+    let (corner1, corner2) = tile_corners(rect);
+    return Shape::Rect { corner1, corner2 };
+}
 
-        set_camera(&camera);
+// The loop, the best rectangle filled in, and the next best ones outlined
+fn map_scene(
+    polygon: &RectilinearPolygon,
+    pairs: &[TwoDimensionalLocationPair],
+    top_n: usize,
+) -> Scene {
+    let bounds =
+        Rect::bounding_box(&polygon.vertices).expect("Expected the loop to have red tiles");
+    let (view_min, view_max) = tile_corners(&bounds);
+    let mut scene = Scene::new().with_view(view_min, view_max);
+
+    let points = polygon.vertices.iter().map(tile_centre).collect();
+    scene.layer("loop").add_labelled(
+        Shape::Polygon { points },
+        Style::stroke(Colour::BLUE, 2.0).with_fill(Colour::rgb(200, 240, 200)),
+        &format!("Loop of {} red tiles", polygon.vertices.len()),
+    );
+
+    // The same rectangle can come from more than one pair of red tiles
+    let mut ranked: Vec<(&TwoDimensionalLocationPair, Rect<2>)> = vec![];
+    for pair in pairs {
+        if ranked.len() == top_n {
+            break;
+        }
+        let rect = pair.bounding_box();
+        if !ranked.iter().any(|(_, other)| *other == rect) {
+            ranked.push((pair, rect));
+        }
+    }
+    let label = |rank: usize, pair: &TwoDimensionalLocationPair| {
+        format!(
+            "#{rank} from {} to {}, area {}",
+            pair.loc1,
+            pair.loc2,
+            pair.inclusive_area()
+        )
+    };
+
+    // Worst first, so that the better ones are drawn on top
+    let candidates = scene.layer("candidates");
+    for (i, (pair, rect)) in ranked.iter().enumerate().skip(1).rev() {
+        let colour = CANDIDATE_COLOURS[(i - 1) % CANDIDATE_COLOURS.len()];
+        candidates.add_labelled(
+            tile_rect(rect),
+            Style::stroke(colour, 2.0),
+            &label(i + 1, pair),
+        );
+    }
 
-        next_frame().await
+    if let Some((pair, rect)) = ranked.first() {
+        scene.layer("best").add_labelled(
+            tile_rect(rect),
+            Style::stroke(Colour::RED, 2.0).with_fill(Colour::RED),
+            &label(1, pair),
+        );
     }
-}
 
-fn fit_camera_to_bounds(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Camera2D {
-    let width = max_x - min_x;
-    let height = max_y - min_y;
+    // Best first, with the red tiles at the corners marked
+    for (i, (pair, rect)) in ranked.iter().enumerate() {
+        let label = label(i + 1, pair);
+        let mut items = vec![Item {
+            shape: tile_rect(rect),
+            style: Style::stroke(Colour::RED, 3.0).with_fill(Colour::RED.with_alpha(100)),
+            label: Some(label.clone()),
+        }];
+        for corner in [pair.loc1, pair.loc2] {
+            items.push(Item {
+                shape: Shape::Marker {
+                    position: tile_centre(&corner),
+                    size: 10.0,
+                },
+                style: Style::fill(Colour::RED),
+                label: Some(format!("Red tile {corner}")),
+            });
+        }
+        scene.add_step(&label, items);
+    }
 
-    // Center of the world
-    let center_x = (max_x - min_x) * 0.5;
-    let center_y = (max_y - min_y) * 0.5;
-    // let target = vec2(center_x, center_y);
-    let mut target = vec2(center_x, center_y);
-    let nudge = vec2(0.0, 50000.0);
-    target += nudge;
+    return scene;
+}
 
-    // Zoom to fit bounds
-    let zoom = (screen_width() / width).min(screen_height() / height);
+fn render_svg(
+    polygon: &RectilinearPolygon,
+    pairs: &[TwoDimensionalLocationPair],
+    top_n: usize,
+) -> String {
+    return svg::render(&map_scene(polygon, pairs, top_n));
+}
 
-    Camera2D {
-        target: target,
-        zoom: vec2(zoom / screen_width(), -zoom / screen_height()),
-        ..Default::default()
-    }
+fn write_to_file(path: &str, text: &str) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
 }
 
-#[macroquad::main("Display")]
-async fn main() {
+fn main() {
     let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
     // self made
     let _contents = "1,1
@@ -495,7 +633,7 @@ async fn main() {
 7,5
 7,4
 1,4"
-                .to_string();
+    .to_string();
     // example
     let _contents = "7,1
 11,1
@@ -504,20 +642,34 @@ async fn main() {
 9,5
 2,5
 2,3
-7,3".to_string();
-
+7,3"
+    .to_string();
 
     let do_part1 = false;
     let do_part2 = true;
+    let write_svg = true;
+    // Needs a display, so off unless looking at it by hand
+    let show_window = false;
     if do_part1 {
         let result1 = part1(&contents);
         println!("Part1 result {result1:?}");
     }
 
     if do_part2 {
-        let (result2, locations, pairs) = part2(&contents);
-        draw_map(&locations, &pairs).await;
+        let (result2, polygon, pairs) = part2(&contents);
         println!("Part2 result {result2:?}");
+
+        if write_svg {
+            write_to_file("map.svg", &render_svg(&polygon, &pairs, TOP_PAIRS))
+                .expect("Expected to write the SVG file");
+        }
+
+        if show_window {
+            macroquad::Window::new(
+                "Display",
+                viewer::show(map_scene(&polygon, &pairs, TOP_PAIRS)),
+            );
+        }
     }
 }
 
@@ -554,6 +706,375 @@ mod tests {
         assert_eq!(result, Some(Answer { answer: 50 }));
     }
 
+    #[test]
+    fn test_inclusive_area() {
+        let pair = |x1, y1, x2, y2| TwoDimensionalLocationPair {
+            loc1: Point::new([x1, y1]),
+            loc2: Point::new([x2, y2]),
+        };
+
+        // The same rectangle, whichever order the corners come in
+        assert_eq!(pair(2, 5, 11, 1).inclusive_area(), 50);
+        assert_eq!(pair(11, 1, 2, 5).inclusive_area(), 50);
+        assert_eq!(pair(2, 1, 11, 5).inclusive_area(), 50);
+        assert_eq!(pair(11, 5, 2, 1).inclusive_area(), 50);
+
+        // A single row, a single column, and a single tile
+        assert_eq!(pair(2, 3, 7, 3).inclusive_area(), 6);
+        assert_eq!(pair(7, 1, 7, 3).inclusive_area(), 3);
+        assert_eq!(pair(4, 4, 4, 4).inclusive_area(), 1);
+
+        // Big enough that the product would overflow an i64
+        assert_eq!(
+            pair(0, 0, 4_000_000_000, 4_000_000_000).inclusive_area(),
+            4_000_000_001 * 4_000_000_001
+        );
+
+        let mut pairs = [pair(0, 0, 1, 1), pair(0, 0, 9, 0), pair(7, 1, 7, 3)];
+        pairs.sort();
+        assert_eq!(
+            pairs
+                .iter()
+                .map(|p| p.inclusive_area())
+                .collect::<Vec<u128>>(),
+            vec![3, 4, 10]
+        );
+    }
+
+    fn parse_polygon(text: &str) -> RectilinearPolygon {
+        let locations = parse_locations(&text.split_whitespace().collect::<Vec<&str>>().join("\n"));
+        return RectilinearPolygon::new(locations).expect("Expected a valid loop");
+    }
+
+    // Brute force: draw the loop at double size so that lines next to each
+    // other still leave a gap between them, then flood fill the outside
+    fn tile_fill(locations: &[Point<2>]) -> Vec<Point<2>> {
+        let bounds = Rect::bounding_box(locations).expect("Expected some locations");
+        let (min_x, min_y) = (bounds.min.x() - 1, bounds.min.y() - 1);
+        let width = (2 * (bounds.max.x() + 1 - min_x) + 1) as usize;
+        let height = (2 * (bounds.max.y() + 1 - min_y) + 1) as usize;
+        let cell = |x: i64, y: i64| (2 * (y - min_y)) as usize * width + (2 * (x - min_x)) as usize;
+
+        let mut on_loop = vec![false; width * height];
+        let mut prev_location = &locations[locations.len() - 1];
+        for location in locations {
+            let (start, end) = (
+                cell(prev_location.x(), prev_location.y()),
+                cell(location.x(), location.y()),
+            );
+            let stride = if prev_location.x() == location.x() {
+                width
+            } else {
+                1
+            };
+            for index in (start.min(end)..=start.max(end)).step_by(stride) {
+                on_loop[index] = true;
+            }
+            prev_location = location;
+        }
+
+        let mut outside = vec![false; width * height];
+        let mut to_visit = vec![0];
+        outside[0] = true;
+        while let Some(index) = to_visit.pop() {
+            let (x, y) = (index % width, index / width);
+            let mut neighbours = vec![];
+            if x > 0 {
+                neighbours.push(index - 1)
+            }
+            if x + 1 < width {
+                neighbours.push(index + 1)
+            }
+            if y > 0 {
+                neighbours.push(index - width)
+            }
+            if y + 1 < height {
+                neighbours.push(index + width)
+            }
+            for neighbour in neighbours {
+                if !on_loop[neighbour] && !outside[neighbour] {
+                    outside[neighbour] = true;
+                    to_visit.push(neighbour);
+                }
+            }
+        }
+
+        let mut tiles = vec![];
+        for y in bounds.min.y()..=bounds.max.y() {
+            for x in bounds.min.x()..=bounds.max.x() {
+                if !outside[cell(x, y)] {
+                    tiles.push(Point::new([x, y]));
+                }
+            }
+        }
+        return tiles;
+    }
+
+    fn check_against_tile_fill(polygon: &RectilinearPolygon) {
+        let locations = &polygon.vertices;
+        let grid = CompressedGrid::new(polygon);
+        let tiles = tile_fill(locations);
+        let bounds = Rect::bounding_box(locations).expect("Expected some locations");
+
+        // Every rectangle in and just around the loop
+        for min_x in bounds.min.x() - 1..=bounds.max.x() + 1 {
+            for max_x in min_x..=bounds.max.x() + 1 {
+                for min_y in bounds.min.y() - 1..=bounds.max.y() + 1 {
+                    for max_y in min_y..=bounds.max.y() + 1 {
+                        let rect = Rect::from_corners(
+                            Point::new([min_x, min_y]),
+                            Point::new([max_x, max_y]),
+                        );
+                        let filled =
+                            tiles.iter().filter(|tile| rect.contains(tile)).count() as u128;
+                        let expected = filled == rect.extent(0) * rect.extent(1);
+                        assert_eq!(
+                            grid.contains_rect(&rect),
+                            expected,
+                            "For {rect:?} in {locations:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_rect_example() {
+        let setup = Setup::new();
+        let polygon = RectilinearPolygon::new(parse_locations(&setup.contents))
+            .expect("Expected a valid loop");
+        let grid = CompressedGrid::new(&polygon);
+
+        assert!(grid.contains_rect(&Rect::from_corners(Point::new([9, 5]), Point::new([2, 3]))));
+        assert!(grid.contains_rect(&Rect::from_corners(Point::new([7, 3]), Point::new([11, 1]))));
+        assert!(!grid.contains_rect(&Rect::from_corners(Point::new([7, 1]), Point::new([11, 7]))));
+        assert!(!grid.contains_rect(&Rect::from_corners(Point::new([2, 5]), Point::new([11, 1]))));
+        check_against_tile_fill(&polygon);
+    }
+
+    #[test]
+    fn test_contains_rect_touching_edges() {
+        // Two edges right next to each other, so there is no tile between them
+        let polygon = parse_polygon("0,0 4,0 4,4 3,4 3,1 2,1 2,4 0,4");
+        let grid = CompressedGrid::new(&polygon);
+        assert!(grid.contains_rect(&Rect::from_corners(Point::new([0, 0]), Point::new([4, 4]))));
+        check_against_tile_fill(&polygon);
+
+        // The same, but with a pocket of outside tiles at the end of the gap
+        let polygon = parse_polygon("0,0 8,0 8,8 4,8 4,6 6,6 6,2 2,2 2,6 3,6 3,8 0,8");
+        let grid = CompressedGrid::new(&polygon);
+        assert!(!grid.contains_rect(&Rect::from_corners(Point::new([0, 0]), Point::new([8, 8]))));
+        assert!(grid.contains_rect(&Rect::from_corners(Point::new([0, 6]), Point::new([8, 8]))));
+        check_against_tile_fill(&polygon);
+    }
+
+    #[test]
+    fn test_contains_rect_notches() {
+        // Concave notch, with extra corners along straight edges
+        check_against_tile_fill(&parse_polygon("0,0 3,0 6,0 6,5 4,5 4,2 2,2 2,5 0,5 0,3"));
+        // Staircase, going the other way round
+        check_against_tile_fill(&parse_polygon("0,0 0,4 2,4 2,3 3,3 3,2 4,2 4,1 5,1 5,0"));
+        // Self made
+        check_against_tile_fill(&parse_polygon(
+            "1,1 9,1 9,9 8,9 8,10 6,10 6,9 1,9 1,5 7,5 7,4 1,4",
+        ));
+    }
+
+    #[test]
+    fn test_polygon_shape() {
+        let setup = Setup::new();
+        let polygon = RectilinearPolygon::new(parse_locations(&setup.contents))
+            .expect("Expected a valid loop");
+        assert_eq!(polygon.twice_signed_area(), 60);
+        assert_eq!(polygon.orientation(), Orientation::Anticlockwise);
+
+        use VertexKind::*;
+        assert_eq!(
+            polygon.vertex_kinds(),
+            vec![
+                Convex, Convex, Convex, Convex, Reflex, Convex, Convex, Reflex
+            ]
+        );
+
+        // Going the other way round flips the area, but not what the corners are
+        let mut reversed = polygon.vertices.clone();
+        reversed.reverse();
+        let reversed = RectilinearPolygon::new(reversed).expect("Expected a valid loop");
+        assert_eq!(reversed.twice_signed_area(), -60);
+        assert_eq!(reversed.orientation(), Orientation::Clockwise);
+        assert_eq!(
+            reversed.vertex_kinds(),
+            vec![
+                Reflex, Convex, Convex, Reflex, Convex, Convex, Convex, Convex
+            ]
+        );
+
+        let straight = parse_polygon("0,0 3,0 6,0 6,5 0,5");
+        assert_eq!(
+            straight.vertex_kinds(),
+            vec![Convex, Straight, Convex, Convex, Convex]
+        );
+    }
+
+    #[test]
+    fn test_polygon_errors() {
+        let polygon = |text: &str| {
+            RectilinearPolygon::new(parse_locations(
+                &text.split_whitespace().collect::<Vec<&str>>().join("\n"),
+            ))
+        };
+
+        assert_eq!(
+            polygon("0,0 1,0 1,1").err(),
+            Some(PolygonError::TooFewVertices { count: 3 })
+        );
+        assert_eq!(
+            polygon("0,0 2,0 2,0 2,2 0,2").err(),
+            Some(PolygonError::RepeatedVertex {
+                index: 2,
+                location: Point::new([2, 0])
+            })
+        );
+        assert_eq!(
+            polygon("0,0 2,0 3,2 0,2").err(),
+            Some(PolygonError::DiagonalEdge {
+                from: Point::new([2, 0]),
+                to: Point::new([3, 2])
+            })
+        );
+
+        let error = polygon("0,0 2,0 2,2 1,2").err();
+        assert_eq!(
+            error,
+            Some(PolygonError::NotClosed {
+                first: Point::new([0, 0]),
+                last: Point::new([1, 2])
+            })
+        );
+        assert_eq!(
+            error.expect("Expected an error").to_string(),
+            "the loop can't be closed, last red tile 1,2 is not in the same row or column as first red tile 0,0"
+        );
+
+        // Crossing over another line
+        assert_eq!(
+            polygon("0,0 3,0 3,2 1,2 1,-1 0,-1").err(),
+            Some(PolygonError::SelfIntersection {
+                edge1: Segment::new(Point::new([0, 0]), Point::new([3, 0])),
+                edge2: Segment::new(Point::new([1, 2]), Point::new([1, -1])),
+            })
+        );
+        // Doubling back on itself
+        assert_eq!(
+            polygon("0,0 4,0 2,0 2,2 0,2").err(),
+            Some(PolygonError::SelfIntersection {
+                edge1: Segment::new(Point::new([0, 0]), Point::new([4, 0])),
+                edge2: Segment::new(Point::new([4, 0]), Point::new([2, 0])),
+            })
+        );
+        // Touching another line at a corner
+        assert!(matches!(
+            polygon("0,0 2,0 2,2 4,2 4,4 2,4 2,2 0,2").err(),
+            Some(PolygonError::SelfIntersection { .. })
+        ));
+    }
+
+    #[test]
+    fn test_best_pairs() {
+        // Every pair both ways round, checked and sorted, then the repeated
+        // rectangles dropped
+        let brute_force = |polygon: &RectilinearPolygon, count: usize| {
+            let grid = CompressedGrid::new(polygon);
+            let mut pairs: Vec<TwoDimensionalLocationPair> = create_pairs(&polygon.vertices)
+                .into_iter()
+                .filter(|pair| grid.contains_rect(&pair.bounding_box()))
+                .collect();
+            pairs.sort();
+            pairs.reverse();
+            let mut best: Vec<TwoDimensionalLocationPair> = vec![];
+            for pair in pairs {
+                if !best
+                    .iter()
+                    .any(|other| other.bounding_box() == pair.bounding_box())
+                {
+                    best.push(pair);
+                }
+            }
+            best.truncate(count);
+            best
+        };
+
+        let setup = Setup::new();
+        let polygons = [
+            RectilinearPolygon::new(parse_locations(&setup.contents))
+                .expect("Expected a valid loop"),
+            parse_polygon("1,1 9,1 9,9 8,9 8,10 6,10 6,9 1,9 1,5 7,5 7,4 1,4"),
+            parse_polygon("0,0 3,0 6,0 6,5 4,5 4,2 2,2 2,5 0,5 0,3"),
+        ];
+        for polygon in &polygons {
+            for count in [0, 1, 3, TOP_PAIRS, 100] {
+                assert_eq!(best_pairs(polygon, count), brute_force(polygon, count));
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_scene() {
+        let setup = Setup::new();
+        let (_, polygon, pairs) = part2(&setup.contents);
+        let scene = map_scene(&polygon, &pairs, 3);
+
+        // Whole tiles are shown, so the edges are half a tile out
+        assert_eq!(
+            scene.view,
+            Some((Position::new(1.5, 0.5), Position::new(11.5, 7.5)))
+        );
+
+        let names: Vec<&str> = scene
+            .layers
+            .iter()
+            .map(|layer| layer.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["loop", "candidates", "best"]);
+        assert_eq!(scene.layers[1].items.len(), 2);
+        assert_eq!(
+            scene.layers[2].items[0].shape,
+            Shape::Rect {
+                corner1: Position::new(1.5, 2.5),
+                corner2: Position::new(9.5, 5.5)
+            }
+        );
+
+        // One step for each rectangle, best first
+        let labels: Vec<&str> = scene.steps.iter().map(|step| step.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "#1 from 9,5 to 2,3, area 24",
+                "#2 from 11,1 to 9,7, area 21",
+                "#3 from 7,3 to 2,5, area 18"
+            ]
+        );
+        assert_eq!(
+            scene.steps[0].items[1].shape,
+            Shape::Marker {
+                position: Position::new(9.0, 5.0),
+                size: 10.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_svg_snapshot() {
+        let setup = Setup::new();
+        let (_, polygon, pairs) = part2(&setup.contents);
+        testing::assert_svg_snapshot(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/svg/example_map.svg"),
+            &render_svg(&polygon, &pairs, TOP_PAIRS),
+        );
+    }
+
     #[test]
     fn test_part1() {
         let contents = LocalFileInputGetter { path: "input.txt" }.get_input();
@@ -584,7 +1105,7 @@ mod tests {
 7,5
 7,4
 1,4"
-                .to_string();
+        .to_string();
         let (result, _, _) = part2(&contents);
         dbg!(&result);
         assert_eq!(result, Some(Answer { answer: 32 }));
@@ -0,0 +1,349 @@
+use std::{
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<const D: usize> {
+    pub coords: [i64; D],
+}
+
+impl<const D: usize> Point<D> {
+    pub fn new(coords: [i64; D]) -> Self {
+        Self { coords }
+    }
+
+    pub fn origin() -> Self {
+        Self { coords: [0; D] }
+    }
+
+    // Differences are taken as i128, so they can never overflow
+    fn differences(&self, other: &Point<D>) -> impl Iterator<Item = i128> {
+        return self
+            .coords
+            .iter()
+            .zip(other.coords)
+            .map(|(a, b)| *a as i128 - b as i128);
+    }
+
+    // Exact, so it is the one to use for comparing distances
+    pub fn squared_euclidean_distance(&self, other: &Point<D>) -> i128 {
+        return self.differences(other).map(|diff| diff * diff).sum();
+    }
+
+    pub fn euclidean_distance(&self, other: &Point<D>) -> f64 {
+        return (self.squared_euclidean_distance(other) as f64).sqrt();
+    }
+
+    pub fn manhattan_distance(&self, other: &Point<D>) -> i128 {
+        return self.differences(other).map(|diff| diff.abs()).sum();
+    }
+
+    pub fn chebyshev_distance(&self, other: &Point<D>) -> i128 {
+        return self
+            .differences(other)
+            .map(|diff| diff.abs())
+            .max()
+            .unwrap_or(0);
+    }
+}
+
+// Named coordinates only exist for the dimensions that have them, so using
+// one that doesn't is a compile error rather than an index panic
+impl Point<2> {
+    pub fn x(&self) -> i64 {
+        return self.coords[0];
+    }
+
+    pub fn y(&self) -> i64 {
+        return self.coords[1];
+    }
+}
+
+impl Point<3> {
+    pub fn x(&self) -> i64 {
+        return self.coords[0];
+    }
+
+    pub fn y(&self) -> i64 {
+        return self.coords[1];
+    }
+
+    pub fn z(&self) -> i64 {
+        return self.coords[2];
+    }
+}
+
+impl<const D: usize> From<[i64; D]> for Point<D> {
+    fn from(coords: [i64; D]) -> Self {
+        Self { coords }
+    }
+}
+
+impl<const D: usize> Add for Point<D> {
+    type Output = Point<D>;
+
+    fn add(self, other: Point<D>) -> Point<D> {
+        return Point::new(std::array::from_fn(|i| self.coords[i] + other.coords[i]));
+    }
+}
+
+impl<const D: usize> Sub for Point<D> {
+    type Output = Point<D>;
+
+    fn sub(self, other: Point<D>) -> Point<D> {
+        return Point::new(std::array::from_fn(|i| self.coords[i] - other.coords[i]));
+    }
+}
+
+impl<const D: usize> Neg for Point<D> {
+    type Output = Point<D>;
+
+    fn neg(self) -> Point<D> {
+        return Point::new(self.coords.map(|coord| -coord));
+    }
+}
+
+impl<const D: usize> Mul<i64> for Point<D> {
+    type Output = Point<D>;
+
+    fn mul(self, scale: i64) -> Point<D> {
+        return Point::new(self.coords.map(|coord| coord * scale));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsePointError {
+    WrongDimension { expected: usize, found: usize },
+    InvalidCoordinate(String),
+}
+
+impl fmt::Display for ParsePointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsePointError::WrongDimension { expected, found } => {
+                write!(f, "expected {expected} coordinates, found {found}")
+            }
+            ParsePointError::InvalidCoordinate(text) => {
+                write!(f, "'{text}' is not a valid coordinate")
+            }
+        }
+    }
+}
+
+// Parses comma separated coordinates, such as "162,817,812"
+impl<const D: usize> FromStr for Point<D> {
+    type Err = ParsePointError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = text.trim().split(',').map(|part| part.trim()).collect();
+        if parts.len() != D {
+            return Err(ParsePointError::WrongDimension {
+                expected: D,
+                found: parts.len(),
+            });
+        }
+
+        let mut coords = [0; D];
+        for (coord, part) in coords.iter_mut().zip(parts) {
+            *coord = part
+                .parse()
+                .map_err(|_| ParsePointError::InvalidCoordinate(part.to_string()))?;
+        }
+        return Ok(Point::new(coords));
+    }
+}
+
+impl<const D: usize> fmt::Display for Point<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let coords: Vec<String> = self.coords.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", coords.join(","))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Segment<const D: usize> {
+    pub start: Point<D>,
+    pub end: Point<D>,
+}
+
+impl<const D: usize> Segment<D> {
+    pub fn new(start: Point<D>, end: Point<D>) -> Self {
+        Self { start, end }
+    }
+
+    pub fn bounding_box(&self) -> Rect<D> {
+        return Rect::from_corners(self.start, self.end);
+    }
+
+    // Which axis the segment runs along, if it only changes along one
+    pub fn axis(&self) -> Option<usize> {
+        let changing: Vec<usize> = (0..D)
+            .filter(|i| self.start.coords[*i] != self.end.coords[*i])
+            .collect();
+        return match changing[..] {
+            [axis] => Some(axis),
+            _ => None,
+        };
+    }
+}
+
+// An axis aligned box, where both min and max are included in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect<const D: usize> {
+    pub min: Point<D>,
+    pub max: Point<D>,
+}
+
+impl<const D: usize> Rect<D> {
+    // The corners can be given in any order
+    pub fn from_corners(corner1: Point<D>, corner2: Point<D>) -> Self {
+        Self {
+            min: Point::new(std::array::from_fn(|i| {
+                corner1.coords[i].min(corner2.coords[i])
+            })),
+            max: Point::new(std::array::from_fn(|i| {
+                corner1.coords[i].max(corner2.coords[i])
+            })),
+        }
+    }
+
+    pub fn bounding_box<'a>(points: impl IntoIterator<Item = &'a Point<D>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        return Some(
+            points.fold(Rect::from_corners(first, first), |rect, point| {
+                Rect::from_corners(
+                    Point::new(std::array::from_fn(|i| {
+                        rect.min.coords[i].min(point.coords[i])
+                    })),
+                    Point::new(std::array::from_fn(|i| {
+                        rect.max.coords[i].max(point.coords[i])
+                    })),
+                )
+            }),
+        );
+    }
+
+    // Number of whole cells covered along the axis, counting both ends
+    pub fn extent(&self, axis: usize) -> u128 {
+        return (self.max.coords[axis] as i128 - self.min.coords[axis] as i128 + 1) as u128;
+    }
+
+    pub fn contains(&self, point: &Point<D>) -> bool {
+        return (0..D).all(|i| {
+            self.min.coords[i] <= point.coords[i] && point.coords[i] <= self.max.coords[i]
+        });
+    }
+
+    // Inside, and not on the boundary
+    pub fn strictly_contains(&self, point: &Point<D>) -> bool {
+        return (0..D)
+            .all(|i| self.min.coords[i] < point.coords[i] && point.coords[i] < self.max.coords[i]);
+    }
+
+    pub fn intersects(&self, other: &Rect<D>) -> bool {
+        return (0..D).all(|i| {
+            self.min.coords[i] <= other.max.coords[i] && other.min.coords[i] <= self.max.coords[i]
+        });
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let a = Point::new([1, 2, 3]);
+        let b = Point::new([4, -2, 3]);
+        assert_eq!(a.squared_euclidean_distance(&b), 25);
+        assert_eq!(a.euclidean_distance(&b), 5.0);
+        assert_eq!(a.manhattan_distance(&b), 7);
+        assert_eq!(a.chebyshev_distance(&b), 4);
+
+        let far = Point::new([i64::MAX, i64::MIN]);
+        let near = Point::new([i64::MIN, 0]);
+        assert_eq!(far.manhattan_distance(&near), u64::MAX as i128 + (1 << 63));
+
+        // Far enough apart that the squared distance would overflow an i64
+        let huge = Point::new([i32::MAX as i64; 3]);
+        let negative = Point::new([i32::MIN as i64; 3]);
+        assert_eq!(
+            huge.squared_euclidean_distance(&negative),
+            3 * (u32::MAX as i128).pow(2)
+        );
+    }
+
+    #[test]
+    fn test_named_coordinates() {
+        let flat = Point::new([1, 2]);
+        assert_eq!((flat.x(), flat.y()), (1, 2));
+        let point = Point::new([1, 2, 3]);
+        assert_eq!((point.x(), point.y(), point.z()), (1, 2, 3));
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("162,817,812".parse(), Ok(Point::new([162, 817, 812])));
+        assert_eq!(" 7, -1 ".parse(), Ok(Point::new([7, -1])));
+        assert_eq!(
+            "1,2".parse::<Point<3>>(),
+            Err(ParsePointError::WrongDimension {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            "1,a".parse::<Point<2>>(),
+            Err(ParsePointError::InvalidCoordinate("a".to_string()))
+        );
+        assert_eq!(Point::new([3, -4]).to_string(), "3,-4");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Point::new([1, 2]);
+        let b = Point::new([10, 20]);
+        assert_eq!(a + b, Point::new([11, 22]));
+        assert_eq!(b - a, Point::new([9, 18]));
+        assert_eq!(-a, Point::new([-1, -2]));
+        assert_eq!(a * 3, Point::new([3, 6]));
+        assert_eq!(Point::<2>::origin(), Point::new([0, 0]));
+    }
+
+    #[test]
+    fn test_rect_and_segment() {
+        let rect = Rect::from_corners(Point::new([11, 1]), Point::new([2, 5]));
+        assert_eq!(rect.min, Point::new([2, 1]));
+        assert_eq!(rect.max, Point::new([11, 5]));
+        assert_eq!((rect.extent(0), rect.extent(1)), (10, 5));
+        assert!(rect.contains(&Point::new([2, 3])));
+        assert!(!rect.strictly_contains(&Point::new([2, 3])));
+        assert!(rect.strictly_contains(&Point::new([3, 3])));
+        assert!(rect.intersects(&Rect::from_corners(
+            Point::new([11, 5]),
+            Point::new([20, 20])
+        )));
+        assert!(!rect.intersects(&Rect::from_corners(
+            Point::new([12, 0]),
+            Point::new([20, 20])
+        )));
+
+        let points = [Point::new([7, 1]), Point::new([11, 7]), Point::new([2, 3])];
+        assert_eq!(
+            Rect::bounding_box(&points),
+            Some(Rect::from_corners(Point::new([2, 1]), Point::new([11, 7])))
+        );
+
+        let segment = Segment::new(Point::new([9, 7]), Point::new([9, 5]));
+        assert_eq!(segment.axis(), Some(1));
+        assert_eq!(segment.bounding_box().extent(1), 3);
+        assert_eq!(
+            Segment::new(Point::origin(), Point::new([1, 1])).axis(),
+            None
+        );
+    }
+}
@@ -0,0 +1,413 @@
+pub mod svg;
+pub mod viewer;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Position {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<(f64, f64)> for Position {
+    fn from((x, y): (f64, f64)) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Colour {
+    pub const WHITE: Colour = Colour::rgb(255, 255, 255);
+    pub const BLACK: Colour = Colour::rgb(0, 0, 0);
+    pub const RED: Colour = Colour::rgb(230, 41, 55);
+    pub const GREEN: Colour = Colour::rgb(0, 228, 48);
+    pub const BLUE: Colour = Colour::rgb(0, 121, 241);
+    pub const ORANGE: Colour = Colour::rgb(255, 161, 0);
+    pub const PURPLE: Colour = Colour::rgb(112, 31, 126);
+    pub const YELLOW: Colour = Colour::rgb(253, 249, 0);
+    pub const GRAY: Colour = Colour::rgb(130, 130, 130);
+    pub const LIME: Colour = Colour::rgb(0, 158, 47);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+}
+
+// Where shapes are, and how big they are, is in world units. Line widths and
+// text sizes are in pixels, so they look the same however far zoomed in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Line {
+        from: Position,
+        to: Position,
+    },
+    // Corners can be given in any order
+    Rect {
+        corner1: Position,
+        corner2: Position,
+    },
+    Circle {
+        centre: Position,
+        radius: f64,
+    },
+    Polygon {
+        points: Vec<Position>,
+    },
+    // Position is the left end of the text's baseline
+    Text {
+        position: Position,
+        text: String,
+        size: f64,
+    },
+    // A dot that stays the same number of pixels across at any zoom
+    Marker {
+        position: Position,
+        size: f64,
+    },
+}
+
+impl Shape {
+    // Smallest and largest corners of the box around the shape
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        let points = match self {
+            Shape::Line { from, to } => vec![*from, *to],
+            Shape::Rect { corner1, corner2 } => vec![*corner1, *corner2],
+            Shape::Circle { centre, radius } => vec![
+                Position::new(centre.x - radius, centre.y - radius),
+                Position::new(centre.x + radius, centre.y + radius),
+            ],
+            Shape::Polygon { points } => points.clone(),
+            Shape::Text { position, .. } | Shape::Marker { position, .. } => vec![*position],
+        };
+        return bounds_of(points);
+    }
+
+    // Whether a point is on the shape, counting anything within tolerance
+    // of a line. Both are in world units, as is the size of a pixel.
+    pub fn contains(&self, point: Position, tolerance: f64, pixel: f64) -> bool {
+        return match self {
+            Shape::Line { from, to } => distance_to_line(point, *from, *to) <= tolerance,
+            Shape::Rect { .. } => {
+                let (min, max) = self.bounds().expect("Expected a rect to have bounds");
+                min.x <= point.x && point.x <= max.x && min.y <= point.y && point.y <= max.y
+            }
+            Shape::Circle { centre, radius } => distance(point, *centre) <= *radius,
+            Shape::Polygon { points } => {
+                // Count the edges crossed going right from the point
+                let mut inside = false;
+                let mut prev = match points.last() {
+                    Some(prev) => *prev,
+                    None => return false,
+                };
+                for current in points {
+                    if (current.y > point.y) != (prev.y > point.y) {
+                        let crossing_x = prev.x
+                            + (point.y - prev.y) / (current.y - prev.y) * (current.x - prev.x);
+                        if crossing_x > point.x {
+                            inside = !inside;
+                        }
+                    }
+                    prev = *current;
+                }
+                inside
+            }
+            Shape::Text { .. } => false,
+            Shape::Marker { position, size } => {
+                distance(point, *position) <= size / 2.0 * pixel + tolerance
+            }
+        };
+    }
+}
+
+fn distance(a: Position, b: Position) -> f64 {
+    return (a.x - b.x).hypot(a.y - b.y);
+}
+
+fn distance_to_line(point: Position, from: Position, to: Position) -> f64 {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length_squared = dx * dx + dy * dy;
+    if length_squared == 0.0 {
+        return distance(point, from);
+    }
+
+    // How far along the line the closest point is, kept within the ends
+    let along =
+        (((point.x - from.x) * dx + (point.y - from.y) * dy) / length_squared).clamp(0.0, 1.0);
+    return distance(
+        point,
+        Position::new(from.x + along * dx, from.y + along * dy),
+    );
+}
+
+fn bounds_of(points: impl IntoIterator<Item = Position>) -> Option<(Position, Position)> {
+    return points.into_iter().fold(None, |bounds, point| match bounds {
+        None => Some((point, point)),
+        Some((min, max)) => Some((
+            Position::new(min.x.min(point.x), min.y.min(point.y)),
+            Position::new(max.x.max(point.x), max.y.max(point.y)),
+        )),
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub stroke: Option<Colour>,
+    pub fill: Option<Colour>,
+    pub stroke_width: f64,
+}
+
+impl Style {
+    pub fn stroke(colour: Colour, width: f64) -> Self {
+        Self {
+            stroke: Some(colour),
+            fill: None,
+            stroke_width: width,
+        }
+    }
+
+    pub fn fill(colour: Colour) -> Self {
+        Self {
+            stroke: None,
+            fill: Some(colour),
+            stroke_width: 0.0,
+        }
+    }
+
+    pub fn with_fill(self, colour: Colour) -> Self {
+        Self {
+            fill: Some(colour),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub shape: Shape,
+    pub style: Style,
+    // Shown by the viewer when the mouse is over the item
+    pub label: Option<String>,
+}
+
+// Drawn in the order they were added, so later items go on top
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    pub items: Vec<Item>,
+    pub visible: bool,
+}
+
+impl Layer {
+    pub fn add(&mut self, shape: Shape, style: Style) -> &mut Self {
+        self.items.push(Item {
+            shape,
+            style,
+            label: None,
+        });
+        return self;
+    }
+
+    pub fn add_labelled(&mut self, shape: Shape, style: Style, label: &str) -> &mut Self {
+        self.items.push(Item {
+            shape,
+            style,
+            label: Some(label.to_string()),
+        });
+        return self;
+    }
+}
+
+// Something to pick out, like one of the best few answers. The viewer steps
+// through them in order with the keyboard, and they aren't drawn otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub label: String,
+    pub items: Vec<Item>,
+}
+
+// Everything to draw, kept separate from how it ends up being drawn
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Scene {
+    pub layers: Vec<Layer>,
+    pub background: Option<Colour>,
+    // The area to show, otherwise everything in the scene is fitted in
+    pub view: Option<(Position, Position)>,
+    pub steps: Vec<Step>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_background(mut self, colour: Colour) -> Self {
+        self.background = Some(colour);
+        return self;
+    }
+
+    pub fn with_view(mut self, corner1: Position, corner2: Position) -> Self {
+        self.view = bounds_of([corner1, corner2]);
+        return self;
+    }
+
+    // The layer with this name, added on top of the others if it's new
+    pub fn layer(&mut self, name: &str) -> &mut Layer {
+        let index = match self.layers.iter().position(|layer| layer.name == name) {
+            Some(index) => index,
+            None => {
+                self.layers.push(Layer {
+                    name: name.to_string(),
+                    items: vec![],
+                    visible: true,
+                });
+                self.layers.len() - 1
+            }
+        };
+        return &mut self.layers[index];
+    }
+
+    pub fn add_step(&mut self, label: &str, items: Vec<Item>) {
+        self.steps.push(Step {
+            label: label.to_string(),
+            items,
+        });
+    }
+
+    pub fn visible_items(&self) -> impl Iterator<Item = &Item> {
+        return self
+            .layers
+            .iter()
+            .filter(|layer| layer.visible)
+            .flat_map(|layer| &layer.items);
+    }
+
+    // The area to show, which is the view if there is one
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        if self.view.is_some() {
+            return self.view;
+        }
+        let corners = self
+            .visible_items()
+            .filter_map(|item| item.shape.bounds())
+            .flat_map(|(min, max)| [min, max]);
+        return bounds_of(corners);
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers() {
+        let mut scene = Scene::new();
+        scene.layer("lines").add(
+            Shape::Line {
+                from: Position::new(0.0, 0.0),
+                to: Position::new(1.0, 1.0),
+            },
+            Style::stroke(Colour::BLUE, 1.0),
+        );
+        scene.layer("labels").add(
+            Shape::Text {
+                position: Position::new(0.5, 0.5),
+                text: "hi".to_string(),
+                size: 12.0,
+            },
+            Style::fill(Colour::BLACK),
+        );
+        scene.layer("lines").add(
+            Shape::Circle {
+                centre: Position::new(4.0, 0.0),
+                radius: 2.0,
+            },
+            Style::fill(Colour::RED),
+        );
+
+        let names: Vec<&str> = scene
+            .layers
+            .iter()
+            .map(|layer| layer.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["lines", "labels"]);
+        assert_eq!(scene.layers[0].items.len(), 2);
+        assert_eq!(
+            scene.bounds(),
+            Some((Position::new(0.0, -2.0), Position::new(6.0, 2.0)))
+        );
+
+        // Hidden layers don't count
+        scene.layers[0].visible = false;
+        assert_eq!(scene.visible_items().count(), 1);
+        assert_eq!(
+            scene.bounds(),
+            Some((Position::new(0.5, 0.5), Position::new(0.5, 0.5)))
+        );
+
+        let scene = scene.with_view(Position::new(10.0, 3.0), Position::new(-1.0, 0.0));
+        assert_eq!(
+            scene.bounds(),
+            Some((Position::new(-1.0, 0.0), Position::new(10.0, 3.0)))
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let origin = Position::new(0.0, 0.0);
+        let line = Shape::Line {
+            from: origin,
+            to: Position::new(10.0, 0.0),
+        };
+        assert!(line.contains(Position::new(5.0, 0.5), 1.0, 1.0));
+        assert!(!line.contains(Position::new(12.0, 0.0), 1.0, 1.0));
+
+        let rect = Shape::Rect {
+            corner1: Position::new(4.0, 4.0),
+            corner2: origin,
+        };
+        assert!(rect.contains(Position::new(4.0, 2.0), 0.0, 1.0));
+        assert!(!rect.contains(Position::new(4.1, 2.0), 0.0, 1.0));
+
+        // An L shape, so the notch is outside it
+        let polygon = Shape::Polygon {
+            points: [
+                (0.0, 0.0),
+                (4.0, 0.0),
+                (4.0, 4.0),
+                (2.0, 4.0),
+                (2.0, 2.0),
+                (0.0, 2.0),
+            ]
+            .into_iter()
+            .map(Position::from)
+            .collect(),
+        };
+        assert!(polygon.contains(Position::new(3.0, 3.0), 0.0, 1.0));
+        assert!(polygon.contains(Position::new(1.0, 1.0), 0.0, 1.0));
+        assert!(!polygon.contains(Position::new(1.0, 3.0), 0.0, 1.0));
+
+        // Markers cover more of the world the further out the view is
+        let marker = Shape::Marker {
+            position: origin,
+            size: 10.0,
+        };
+        assert!(!marker.contains(Position::new(3.0, 0.0), 0.0, 0.5));
+        assert!(marker.contains(Position::new(3.0, 0.0), 0.0, 1.0));
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{Colour, Position, Scene, Shape, Style};
+
+// Size of the longest side of the picture, and the gap left around it
+pub const SIZE: f64 = 1000.0;
+pub const MARGIN: f64 = 20.0;
+
+// Lines the scene up with the picture, keeping both axes to the same scale
+struct Canvas {
+    min: Position,
+    scale: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Canvas {
+    fn new(scene: &Scene) -> Self {
+        let origin = Position::new(0.0, 0.0);
+        let (min, max) = scene.bounds().unwrap_or((origin, origin));
+        let (scene_width, scene_height) = (max.x - min.x, max.y - min.y);
+
+        let longest = scene_width.max(scene_height);
+        let scale = if longest > 0.0 {
+            (SIZE - 2.0 * MARGIN) / longest
+        } else {
+            1.0
+        };
+
+        return Canvas {
+            min,
+            scale,
+            width: scene_width * scale + 2.0 * MARGIN,
+            height: scene_height * scale + 2.0 * MARGIN,
+        };
+    }
+
+    fn x(&self, x: f64) -> String {
+        return number(MARGIN + (x - self.min.x) * self.scale);
+    }
+
+    fn y(&self, y: f64) -> String {
+        return number(MARGIN + (y - self.min.y) * self.scale);
+    }
+
+    fn length(&self, length: f64) -> String {
+        return number(length * self.scale);
+    }
+}
+
+// At most two decimal places, without any trailing zeros
+pub fn number(value: f64) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        return "0".to_string();
+    }
+    return text.to_string();
+}
+
+fn colour(colour: &Colour) -> String {
+    return format!("rgb({},{},{})", colour.r, colour.g, colour.b);
+}
+
+fn escape(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+fn style_attributes(style: &Style, filled: bool) -> Vec<(&'static str, String)> {
+    let mut attributes = vec![];
+
+    if filled {
+        match &style.fill {
+            Some(fill) => {
+                attributes.push(("fill", colour(fill)));
+                if fill.a < 255 {
+                    attributes.push(("fill-opacity", number(fill.a as f64 / 255.0)));
+                }
+            }
+            None => attributes.push(("fill", "transparent".to_string())),
+        }
+    }
+
+    if let Some(stroke) = &style.stroke {
+        attributes.push(("stroke", colour(stroke)));
+        if stroke.a < 255 {
+            attributes.push(("stroke-opacity", number(stroke.a as f64 / 255.0)));
+        }
+        attributes.push(("stroke-width", number(style.stroke_width)));
+    }
+
+    return attributes;
+}
+
+// Attributes are always written in name order, the same as the snapshot
+// tests normalise them to
+fn element(
+    name: &str,
+    mut attributes: Vec<(&'static str, String)>,
+    content: Option<&str>,
+) -> String {
+    attributes.sort();
+    let attributes: String = attributes
+        .iter()
+        .map(|(key, value)| format!(" {key}=\"{value}\""))
+        .collect();
+
+    return match content {
+        Some(content) => format!("<{name}{attributes}>{}</{name}>", escape(content)),
+        None => format!("<{name}{attributes}/>"),
+    };
+}
+
+fn shape_element(canvas: &Canvas, shape: &Shape, style: &Style) -> String {
+    return match shape {
+        Shape::Line { from, to } => {
+            let mut attributes = style_attributes(style, false);
+            attributes.extend([
+                ("x1", canvas.x(from.x)),
+                ("y1", canvas.y(from.y)),
+                ("x2", canvas.x(to.x)),
+                ("y2", canvas.y(to.y)),
+            ]);
+            element("line", attributes, None)
+        }
+        Shape::Rect { corner1, corner2 } => {
+            let mut attributes = style_attributes(style, true);
+            attributes.extend([
+                ("x", canvas.x(corner1.x.min(corner2.x))),
+                ("y", canvas.y(corner1.y.min(corner2.y))),
+                ("width", canvas.length((corner1.x - corner2.x).abs())),
+                ("height", canvas.length((corner1.y - corner2.y).abs())),
+            ]);
+            element("rect", attributes, None)
+        }
+        Shape::Circle { centre, radius } => {
+            let mut attributes = style_attributes(style, true);
+            attributes.extend([
+                ("cx", canvas.x(centre.x)),
+                ("cy", canvas.y(centre.y)),
+                ("r", canvas.length(*radius)),
+            ]);
+            element("circle", attributes, None)
+        }
+        Shape::Polygon { points } => {
+            let points: Vec<String> = points
+                .iter()
+                .map(|point| format!("{},{}", canvas.x(point.x), canvas.y(point.y)))
+                .collect();
+            let mut attributes = style_attributes(style, true);
+            attributes.push(("points", points.join(" ")));
+            element("polygon", attributes, None)
+        }
+        Shape::Text {
+            position,
+            text,
+            size,
+        } => {
+            let mut attributes = style_attributes(style, true);
+            attributes.extend([
+                ("x", canvas.x(position.x)),
+                ("y", canvas.y(position.y)),
+                ("font-size", number(*size)),
+            ]);
+            element("text", attributes, Some(text))
+        }
+        Shape::Marker { position, size } => {
+            let mut attributes = style_attributes(style, true);
+            attributes.extend([
+                ("cx", canvas.x(position.x)),
+                ("cy", canvas.y(position.y)),
+                ("r", number(size / 2.0)),
+            ]);
+            element("circle", attributes, None)
+        }
+    };
+}
+
+// The whole scene as an SVG file, scaled to fit the picture
+pub fn render(scene: &Scene) -> String {
+    let canvas = Canvas::new(scene);
+
+    let mut lines = vec![format!(
+        "<svg viewBox=\"0 0 {} {}\" xmlns=\"http://www.w3.org/2000/svg\">",
+        number(canvas.width),
+        number(canvas.height)
+    )];
+
+    if let Some(background) = &scene.background {
+        lines.push(element(
+            "rect",
+            vec![
+                ("fill", colour(background)),
+                ("height", number(canvas.height)),
+                ("width", number(canvas.width)),
+                ("x", "0".to_string()),
+                ("y", "0".to_string()),
+            ],
+            None,
+        ));
+    }
+
+    for layer in scene.layers.iter().filter(|layer| layer.visible) {
+        lines.push(format!("<g id=\"{}\">", escape(&layer.name)));
+        for item in &layer.items {
+            lines.push(shape_element(&canvas, &item.shape, &item.style));
+        }
+        lines.push("</g>".to_string());
+    }
+
+    lines.push("</svg>".to_string());
+    return lines.join("\n") + "\n";
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(20.0), "20");
+        assert_eq!(number(0.5), "0.5");
+        assert_eq!(number(1.0 / 3.0), "0.33");
+        assert_eq!(number(-2.999), "-3");
+        assert_eq!(number(-0.001), "0");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut scene = Scene::new()
+            .with_background(Colour::WHITE)
+            .with_view(Position::new(0.0, 0.0), Position::new(96.0, 48.0));
+        scene
+            .layer("shapes")
+            .add(
+                Shape::Rect {
+                    corner1: Position::new(48.0, 24.0),
+                    corner2: Position::new(0.0, 0.0),
+                },
+                Style::stroke(Colour::BLACK, 5.0),
+            )
+            .add(
+                Shape::Circle {
+                    centre: Position::new(72.0, 24.0),
+                    radius: 12.0,
+                },
+                Style::fill(Colour::RED.with_alpha(51)),
+            )
+            .add(
+                Shape::Marker {
+                    position: Position::new(96.0, 48.0),
+                    size: 8.0,
+                },
+                Style::fill(Colour::BLUE),
+            );
+        scene.layer("labels").add(
+            Shape::Text {
+                position: Position::new(0.0, 48.0),
+                text: "a < b".to_string(),
+                size: 12.0,
+            },
+            Style::fill(Colour::BLACK),
+        );
+        scene.layer("hidden").visible = false;
+        scene.layer("hidden").add(
+            Shape::Line {
+                from: Position::new(0.0, 0.0),
+                to: Position::new(1.0, 1.0),
+            },
+            Style::stroke(Colour::BLUE, 1.0),
+        );
+
+        // 96 wide fits into 960, so everything is 10 times bigger
+        assert_eq!(
+            render(&scene),
+            "<svg viewBox=\"0 0 1000 520\" xmlns=\"http://www.w3.org/2000/svg\">
+<rect fill=\"rgb(255,255,255)\" height=\"520\" width=\"1000\" x=\"0\" y=\"0\"/>
+<g id=\"shapes\">
+<rect fill=\"transparent\" height=\"240\" stroke=\"rgb(0,0,0)\" stroke-width=\"5\" width=\"480\" x=\"20\" y=\"20\"/>
+<circle cx=\"740\" cy=\"260\" fill=\"rgb(230,41,55)\" fill-opacity=\"0.2\" r=\"120\"/>
+<circle cx=\"980\" cy=\"500\" fill=\"rgb(0,121,241)\" r=\"4\"/>
+</g>
+<g id=\"labels\">
+<text fill=\"rgb(0,0,0)\" font-size=\"12\" x=\"20\" y=\"500\">a &lt; b</text>
+</g>
+</svg>
+"
+        );
+    }
+
+    #[test]
+    fn test_render_empty() {
+        assert_eq!(
+            render(&Scene::new()),
+            "<svg viewBox=\"0 0 40 40\" xmlns=\"http://www.w3.org/2000/svg\">\n</svg>\n"
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use crate::{Colour, Item, Position, Scene, Shape, Style, svg};
+use macroquad::prelude::{
+    Color, KeyCode, MouseButton, clear_background, draw_circle, draw_circle_lines, draw_line,
+    draw_rectangle, draw_rectangle_lines, draw_text, is_key_pressed, is_mouse_button_down,
+    mouse_position, mouse_wheel, next_frame, screen_height, screen_width,
+};
+
+// Fraction of the screen the scene fills when it's first shown
+const FIT_FRACTION: f64 = 0.9;
+// How much one notch of the mouse wheel zooms by
+const ZOOM_STEP: f64 = 1.1;
+// How close in pixels the mouse has to be to a line to be over it
+const HOVER_TOLERANCE: f64 = 4.0;
+const INFO_TEXT_SIZE: f32 = 20.0;
+
+// Which part of the world is on screen. Only positions go through this, so
+// line widths stay the same number of pixels at any zoom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    pub centre: Position,
+    // Pixels per world unit
+    pub scale: f64,
+    pub screen_width: f64,
+    pub screen_height: f64,
+}
+
+impl View {
+    pub fn fit(
+        bounds: Option<(Position, Position)>,
+        screen_width: f64,
+        screen_height: f64,
+    ) -> Self {
+        let origin = Position::new(0.0, 0.0);
+        let (min, max) = bounds.unwrap_or((origin, origin));
+        let (width, height) = (max.x - min.x, max.y - min.y);
+
+        let scale = match (width > 0.0, height > 0.0) {
+            (true, true) => (screen_width / width).min(screen_height / height),
+            (true, false) => screen_width / width,
+            (false, true) => screen_height / height,
+            (false, false) => 1.0,
+        };
+
+        return View {
+            centre: Position::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0),
+            scale: scale * FIT_FRACTION,
+            screen_width,
+            screen_height,
+        };
+    }
+
+    pub fn to_screen(&self, position: Position) -> (f32, f32) {
+        let x = (position.x - self.centre.x) * self.scale + self.screen_width / 2.0;
+        let y = (position.y - self.centre.y) * self.scale + self.screen_height / 2.0;
+        return (x as f32, y as f32);
+    }
+
+    pub fn to_world(&self, (x, y): (f32, f32)) -> Position {
+        return Position::new(
+            (x as f64 - self.screen_width / 2.0) / self.scale + self.centre.x,
+            (y as f64 - self.screen_height / 2.0) / self.scale + self.centre.y,
+        );
+    }
+
+    // Moves the world along with the mouse, by a distance in pixels
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.centre.x -= dx / self.scale;
+        self.centre.y -= dy / self.scale;
+    }
+
+    // Zooms in or out while keeping the same bit of the world under the
+    // given point on the screen
+    pub fn zoom_at(&mut self, factor: f64, screen: (f32, f32)) {
+        let before = self.to_world(screen);
+        self.scale *= factor;
+        let after = self.to_world(screen);
+        self.centre.x += before.x - after.x;
+        self.centre.y += before.y - after.y;
+    }
+}
+
+// What the viewer is doing, apart from drawing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewerState {
+    pub view: View,
+    // Which of the scene's steps is picked out, if any
+    pub step: Option<usize>,
+}
+
+impl ViewerState {
+    pub fn new(view: View) -> Self {
+        Self { view, step: None }
+    }
+
+    // Moves forwards or backwards through the steps, wrapping around at the
+    // ends, and brings the new one into the middle of the screen
+    pub fn change_step(&mut self, scene: &Scene, forwards: bool) {
+        let count = scene.steps.len();
+        if count == 0 {
+            return;
+        }
+
+        let step = match (self.step, forwards) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(step), true) => (step + 1) % count,
+            (Some(step), false) => (step + count - 1) % count,
+        };
+        self.step = Some(step);
+
+        let corners = scene.steps[step]
+            .items
+            .iter()
+            .filter_map(|item| item.shape.bounds())
+            .flat_map(|(min, max)| [min, max]);
+        if let Some((min, max)) = crate::bounds_of(corners) {
+            self.view.centre = Position::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+        }
+    }
+
+    // The label of the top most item under the point on the screen
+    pub fn hovered_label<'a>(&self, scene: &'a Scene, screen: (f32, f32)) -> Option<&'a str> {
+        let point = self.view.to_world(screen);
+        let pixel = 1.0 / self.view.scale;
+
+        let step_items = self
+            .step
+            .and_then(|step| scene.steps.get(step))
+            .map_or(&[][..], |step| &step.items[..]);
+        let visible: Vec<&Item> = scene.visible_items().collect();
+
+        return step_items
+            .iter()
+            .rev()
+            .chain(visible.into_iter().rev())
+            .filter(|item| item.shape.contains(point, HOVER_TOLERANCE * pixel, pixel))
+            .find_map(|item| item.label.as_deref());
+    }
+
+    // Lines of text to show in the corner
+    pub fn info(&self, scene: &Scene, mouse: (f32, f32)) -> Vec<String> {
+        let point = self.view.to_world(mouse);
+        let mut lines = vec![format!(
+            "{}, {}",
+            svg::number(point.x),
+            svg::number(point.y)
+        )];
+
+        if let Some(label) = self.hovered_label(scene, mouse) {
+            lines.push(label.to_string());
+        }
+        if let Some(step) = self.step.and_then(|index| scene.steps.get(index)) {
+            let number = self.step.expect("Expected a step") + 1;
+            lines.push(format!("{number}/{}: {}", scene.steps.len(), step.label));
+        }
+        return lines;
+    }
+}
+
+fn to_color(colour: &Colour) -> Color {
+    return Color::from_rgba(colour.r, colour.g, colour.b, colour.a);
+}
+
+fn draw_shape(view: &View, shape: &Shape, style: &Style) {
+    let stroke = style.stroke.as_ref().map(to_color);
+    let fill = style.fill.as_ref().map(to_color);
+    let stroke_width = style.stroke_width as f32;
+
+    match shape {
+        Shape::Line { from, to } => {
+            if let Some(stroke) = stroke {
+                let ((x1, y1), (x2, y2)) = (view.to_screen(*from), view.to_screen(*to));
+                draw_line(x1, y1, x2, y2, stroke_width, stroke);
+            }
+        }
+        Shape::Rect { corner1, corner2 } => {
+            let ((x1, y1), (x2, y2)) = (view.to_screen(*corner1), view.to_screen(*corner2));
+            let (x, y, w, h) = (x1.min(x2), y1.min(y2), (x1 - x2).abs(), (y1 - y2).abs());
+            if let Some(fill) = fill {
+                draw_rectangle(x, y, w, h, fill);
+            }
+            if let Some(stroke) = stroke {
+                draw_rectangle_lines(x, y, w, h, stroke_width, stroke);
+            }
+        }
+        Shape::Circle { centre, radius } => {
+            let (x, y) = view.to_screen(*centre);
+            draw_circle_shape(
+                x,
+                y,
+                (radius * view.scale) as f32,
+                fill,
+                stroke,
+                stroke_width,
+            );
+        }
+        Shape::Marker { position, size } => {
+            let (x, y) = view.to_screen(*position);
+            draw_circle_shape(x, y, *size as f32 / 2.0, fill, stroke, stroke_width);
+        }
+        Shape::Polygon { points } => {
+            if let Some(fill) = fill {
+                let screen_points: Vec<(f32, f32)> =
+                    points.iter().map(|point| view.to_screen(*point)).collect();
+                fill_polygon(&screen_points, fill);
+            }
+            if let Some(stroke) = stroke {
+                let mut prev = points.last().map(|point| view.to_screen(*point));
+                for point in points {
+                    let (x2, y2) = view.to_screen(*point);
+                    if let Some((x1, y1)) = prev {
+                        draw_line(x1, y1, x2, y2, stroke_width, stroke);
+                    }
+                    prev = Some((x2, y2));
+                }
+            }
+        }
+        Shape::Text {
+            position,
+            text,
+            size,
+        } => {
+            let (x, y) = view.to_screen(*position);
+            let colour = fill.or(stroke).unwrap_or(to_color(&Colour::BLACK));
+            draw_text(text, x, y, *size as f32, colour);
+        }
+    }
+}
+
+// Fills one pixel row at a time, so shapes that aren't convex work without
+// cutting them into triangles
+fn fill_polygon(points: &[(f32, f32)], fill: Color) {
+    let top = points.iter().map(|(_, y)| *y).fold(f32::INFINITY, f32::min);
+    let bottom = points
+        .iter()
+        .map(|(_, y)| *y)
+        .fold(f32::NEG_INFINITY, f32::max);
+    if !top.is_finite() || !bottom.is_finite() {
+        return;
+    }
+
+    let first_row = top.floor().max(0.0);
+    let last_row = bottom.ceil().min(screen_height());
+    let mut row = first_row;
+    while row < last_row {
+        for (start, end) in fill_spans(points, row + 0.5) {
+            draw_rectangle(start, row, end - start, 1.0, fill);
+        }
+        row += 1.0;
+    }
+}
+
+// The parts of the horizontal line at y that are inside the polygon. Uses the
+// same nonzero rule as SVG, so both renderers fill the same area.
+fn fill_spans(points: &[(f32, f32)], y: f32) -> Vec<(f32, f32)> {
+    let mut crossings: Vec<(f32, i32)> = vec![];
+    let mut prev = match points.last() {
+        Some(point) => *point,
+        None => return vec![],
+    };
+    for point in points {
+        let ((x1, y1), (x2, y2)) = (prev, *point);
+        // Half open, so a vertex exactly on the line is only counted once
+        if (y1 <= y) != (y2 <= y) {
+            let x = x1 + (y - y1) / (y2 - y1) * (x2 - x1);
+            let winding = if y2 > y1 { 1 } else { -1 };
+            crossings.push((x, winding));
+        }
+        prev = *point;
+    }
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut spans = vec![];
+    let mut winding = 0;
+    for (index, (x, change)) in crossings.iter().enumerate() {
+        winding += change;
+        let Some((next_x, _)) = crossings.get(index + 1) else {
+            continue;
+        };
+        if winding == 0 || next_x <= x {
+            continue;
+        }
+        // Joined on to the last span if there's no gap between them
+        match spans.last_mut() {
+            Some((_, end)) if *end == *x => *end = *next_x,
+            _ => spans.push((*x, *next_x)),
+        }
+    }
+    return spans;
+}
+
+fn draw_circle_shape(
+    x: f32,
+    y: f32,
+    radius: f32,
+    fill: Option<Color>,
+    stroke: Option<Color>,
+    stroke_width: f32,
+) {
+    if let Some(fill) = fill {
+        draw_circle(x, y, radius, fill);
+    }
+    if let Some(stroke) = stroke {
+        draw_circle_lines(x, y, radius, stroke_width, stroke);
+    }
+}
+
+pub fn draw_scene(scene: &Scene, state: &ViewerState) {
+    let background = scene.background.unwrap_or(Colour::WHITE);
+    clear_background(to_color(&background));
+
+    for item in scene.visible_items() {
+        draw_shape(&state.view, &item.shape, &item.style);
+    }
+
+    if let Some(step) = state.step.and_then(|index| scene.steps.get(index)) {
+        for item in &step.items {
+            draw_shape(&state.view, &item.shape, &item.style);
+        }
+    }
+}
+
+// Shows the scene until the window is closed. Scroll to zoom, drag to move
+// around, hover over things to see what they are, left and right arrows to
+// step through the scene's steps, and F to fit everything back on screen.
+pub async fn show(scene: Scene) {
+    let view = View::fit(
+        scene.bounds(),
+        screen_width() as f64,
+        screen_height() as f64,
+    );
+    let mut state = ViewerState::new(view);
+    let mut last_mouse = mouse_position();
+
+    loop {
+        state.view.screen_width = screen_width() as f64;
+        state.view.screen_height = screen_height() as f64;
+
+        let mouse = mouse_position();
+        if is_mouse_button_down(MouseButton::Left) {
+            state.view.pan(
+                (mouse.0 - last_mouse.0) as f64,
+                (mouse.1 - last_mouse.1) as f64,
+            );
+        }
+        last_mouse = mouse;
+
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            state
+                .view
+                .zoom_at(ZOOM_STEP.powf(wheel.signum() as f64), mouse);
+        }
+
+        if is_key_pressed(KeyCode::Right) {
+            state.change_step(&scene, true);
+        }
+        if is_key_pressed(KeyCode::Left) {
+            state.change_step(&scene, false);
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            state.step = None;
+        }
+        if is_key_pressed(KeyCode::F) {
+            state.view = View::fit(
+                scene.bounds(),
+                state.view.screen_width,
+                state.view.screen_height,
+            );
+        }
+
+        draw_scene(&scene, &state);
+        for (i, line) in state.info(&scene, mouse).iter().enumerate() {
+            let y = INFO_TEXT_SIZE * (i + 1) as f32;
+            draw_text(line, 10.0, y, INFO_TEXT_SIZE, to_color(&Colour::BLACK));
+        }
+
+        next_frame().await
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_scene() -> Scene {
+        let mut scene = Scene::new();
+        scene.layer("boxes").add_labelled(
+            Shape::Rect {
+                corner1: Position::new(0.0, 0.0),
+                corner2: Position::new(10.0, 10.0),
+            },
+            Style::stroke(Colour::BLACK, 1.0),
+            "big box",
+        );
+        for (i, x) in [2.0, 6.0].into_iter().enumerate() {
+            let shape = Shape::Rect {
+                corner1: Position::new(x, 2.0),
+                corner2: Position::new(x + 2.0, 4.0),
+            };
+            let label = format!("box {}", i + 1);
+            let item = Item {
+                shape,
+                style: Style::fill(Colour::RED),
+                label: Some(label.clone()),
+            };
+            scene.add_step(&label, vec![item]);
+        }
+        return scene;
+    }
+
+    #[test]
+    fn test_view() {
+        let bounds = Some((Position::new(-10.0, 0.0), Position::new(10.0, 5.0)));
+        let mut view = View::fit(bounds, 800.0, 600.0);
+
+        // 20 wide into 800 pixels, with a bit of room around it
+        assert_eq!(view.scale, 40.0 * FIT_FRACTION);
+        assert_eq!(view.to_screen(Position::new(0.0, 2.5)), (400.0, 300.0));
+        assert_eq!(view.to_world((400.0, 300.0)), Position::new(0.0, 2.5));
+
+        view.scale = 72.0;
+        view.pan(72.0, 0.0);
+        assert_eq!(view.to_world((400.0, 300.0)), Position::new(-1.0, 2.5));
+        assert_eq!(view.to_screen(Position::new(-1.0, 2.5)), (400.0, 300.0));
+
+        let view = View::fit(None, 800.0, 600.0);
+        assert_eq!(view.to_screen(Position::new(0.0, 0.0)), (400.0, 300.0));
+    }
+
+    #[test]
+    fn test_zoom_at() {
+        let bounds = Some((Position::new(0.0, 0.0), Position::new(100.0, 100.0)));
+        let mut view = View::fit(bounds, 800.0, 800.0);
+
+        // Whatever is under the mouse stays there
+        let under_mouse = view.to_world((100.0, 700.0));
+        view.zoom_at(2.0, (100.0, 700.0));
+        let after = view.to_world((100.0, 700.0));
+        assert!((after.x - under_mouse.x).abs() < 1e-9);
+        assert!((after.y - under_mouse.y).abs() < 1e-9);
+        assert_eq!(view.scale, 8.0 * 2.0 * FIT_FRACTION);
+    }
+
+    #[test]
+    fn test_steps_and_hover() {
+        let scene = test_scene();
+        let view = View::fit(scene.bounds(), 100.0, 100.0);
+        let mut state = ViewerState::new(view);
+        let on_second_box = state.view.to_screen(Position::new(7.0, 3.0));
+
+        // Steps aren't there until they're picked
+        assert_eq!(state.hovered_label(&scene, on_second_box), Some("big box"));
+
+        state.change_step(&scene, false);
+        assert_eq!(state.step, Some(1));
+        assert_eq!(state.view.centre, Position::new(7.0, 3.0));
+        let on_second_box = state.view.to_screen(Position::new(7.0, 3.0));
+        assert_eq!(state.hovered_label(&scene, on_second_box), Some("box 2"));
+        assert_eq!(
+            state.info(&scene, on_second_box),
+            vec!["7, 3", "box 2", "2/2: box 2"]
+        );
+
+        // Wraps around both ways
+        state.change_step(&scene, true);
+        assert_eq!(state.step, Some(0));
+        state.change_step(&scene, false);
+        assert_eq!(state.step, Some(1));
+
+        let outside = state.view.to_screen(Position::new(20.0, 3.0));
+        assert_eq!(state.hovered_label(&scene, outside), None);
+    }
+
+    #[test]
+    fn test_fill_spans() {
+        // A U shape, so the middle rows are filled in two parts
+        let points = [
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 8.0),
+            (6.0, 8.0),
+            (6.0, 0.0),
+            (8.0, 0.0),
+            (8.0, 10.0),
+            (0.0, 10.0),
+        ];
+        assert_eq!(fill_spans(&points, 4.0), vec![(0.0, 2.0), (6.0, 8.0)]);
+        assert_eq!(fill_spans(&points, 9.0), vec![(0.0, 8.0)]);
+        assert_eq!(fill_spans(&points, 11.0), vec![]);
+
+        // Which way round the points go doesn't matter
+        let reversed: Vec<(f32, f32)> = points.iter().rev().copied().collect();
+        assert_eq!(fill_spans(&reversed, 4.0), vec![(0.0, 2.0), (6.0, 8.0)]);
+
+        // Nonzero, so a loop going round twice is still filled once
+        let twice = [
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (0.0, 4.0),
+        ];
+        assert_eq!(fill_spans(&twice, 2.0), vec![(0.0, 4.0)]);
+        assert_eq!(fill_spans(&[], 2.0), vec![]);
+    }
+}
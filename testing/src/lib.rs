@@ -0,0 +1,307 @@
+use scene::Scene;
+use std::{env, fs, path::Path};
+
+// Set this to write the output over the golden files instead of checking it
+pub const UPDATE_ENV_VAR: &str = "UPDATE_GOLDENS";
+
+// Lines either side of a change to show in a diff
+const DIFF_CONTEXT: usize = 2;
+
+// Checks some rendered SVG against the golden file, after normalising both.
+// Panics with a diff when they're different.
+pub fn assert_svg_snapshot(golden_path: impl AsRef<Path>, svg: &str) {
+    let golden_path = golden_path.as_ref();
+    let actual = normalise_svg(svg);
+
+    if env::var_os(UPDATE_ENV_VAR).is_some() {
+        if let Some(parent) = golden_path.parent() {
+            fs::create_dir_all(parent).expect("Expected to create the golden file's directory");
+        }
+        fs::write(golden_path, &actual).expect("Expected to write the golden file");
+        return;
+    }
+
+    let Ok(golden) = fs::read_to_string(golden_path) else {
+        panic!(
+            "No golden file at {}, run with {UPDATE_ENV_VAR}=1 to create it",
+            golden_path.display()
+        );
+    };
+    let expected = normalise_svg(&golden);
+
+    if expected != actual {
+        panic!(
+            "SVG doesn't match {}, run with {UPDATE_ENV_VAR}=1 if this is expected\n{}",
+            golden_path.display(),
+            line_diff(&expected, &actual)
+        );
+    }
+}
+
+pub fn assert_scene_snapshot(golden_path: impl AsRef<Path>, scene: &Scene) {
+    assert_svg_snapshot(golden_path, &scene::svg::render(scene));
+}
+
+// One tag per line, with attributes sorted by name and numbers rounded
+pub fn normalise_svg(svg: &str) -> String {
+    let mut lines = vec![];
+    let mut rest = svg;
+
+    while let Some(start) = rest.find('<') {
+        let text = rest[..start].trim();
+        if !text.is_empty() {
+            lines.push(text.to_string());
+        }
+
+        if rest[start..].starts_with("<!--") {
+            let end = rest[start..]
+                .find("-->")
+                .map_or(rest.len(), |end| start + end + 3);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let end = rest[start..]
+            .find('>')
+            .map_or(rest.len(), |end| start + end + 1);
+        lines.push(normalise_tag(&rest[start..end]));
+        rest = &rest[end..];
+    }
+
+    let text = rest.trim();
+    if !text.is_empty() {
+        lines.push(text.to_string());
+    }
+
+    return lines.join("\n") + "\n";
+}
+
+fn normalise_tag(tag: &str) -> String {
+    // Closing tags and declarations are left alone
+    if tag.starts_with("</") || tag.starts_with("<?") || tag.starts_with("<!") {
+        return tag.split_whitespace().collect::<Vec<&str>>().join(" ");
+    }
+
+    let inner = tag.trim_start_matches('<').trim_end_matches('>');
+    let self_closing = inner.ends_with('/');
+    let inner = inner.trim_end_matches('/').trim();
+
+    let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+    let name = &inner[..name_end];
+
+    let mut attributes = parse_attributes(&inner[name_end..]);
+    attributes.sort();
+
+    let mut out = format!("<{name}");
+    for (key, value) in attributes {
+        out += &format!(" {key}=\"{}\"", normalise_numbers(&value));
+    }
+    out += if self_closing { "/>" } else { ">" };
+    return out;
+}
+
+fn parse_attributes(text: &str) -> Vec<(String, String)> {
+    let mut attributes = vec![];
+    let mut rest = text.trim_start();
+
+    while let Some(equals) = rest.find('=') {
+        let key = rest[..equals].trim().to_string();
+        let after = rest[equals + 1..].trim_start();
+
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            // Unquoted, so it runs until the next space
+            let end = after.find(char::is_whitespace).unwrap_or(after.len());
+            attributes.push((key, after[..end].to_string()));
+            rest = after[end..].trim_start();
+            continue;
+        };
+
+        let end = after[1..].find(quote).map_or(after.len(), |end| end + 1);
+        attributes.push((key, after[1..end].to_string()));
+        rest = after[(end + 1).min(after.len())..].trim_start();
+    }
+
+    return attributes;
+}
+
+// Rounds every number in an attribute value, leaving things like colours
+// and ids that only happen to contain digits as they are
+fn normalise_numbers(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let part_of_word =
+            i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '#' || chars[i - 1] == '_');
+        let starts_number = c.is_ascii_digit()
+            || ((c == '-' || c == '.')
+                && chars
+                    .get(i + 1)
+                    .is_some_and(|next| next.is_ascii_digit() || *next == '.'));
+
+        if part_of_word && c.is_alphanumeric() {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        if !starts_number {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        i += 1;
+        let mut seen_point = c == '.';
+        while i < chars.len() && (chars[i].is_ascii_digit() || (chars[i] == '.' && !seen_point)) {
+            seen_point |= chars[i] == '.';
+            i += 1;
+        }
+
+        let number: String = chars[start..i].iter().collect();
+        match number.parse::<f64>() {
+            // Rounded the same way the SVG writer does, so tiny float
+            // differences don't count as a change
+            Ok(parsed) => out += &scene::svg::number(parsed),
+            Err(_) => out += &number,
+        }
+    }
+
+    return out;
+}
+
+// Line by line diff, with - for lines only in expected and + for lines only
+// in actual, and a few unchanged lines around each change
+pub fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence of the lines after each position
+    let mut common = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff: Vec<(char, &str)> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff.push((' ', expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || common[i + 1][j] >= common[i][j + 1])
+        {
+            diff.push(('-', expected[i]));
+            i += 1;
+        } else {
+            diff.push(('+', actual[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..diff.len())
+        .filter(|index| diff[*index].0 != ' ')
+        .collect();
+    let mut out = vec![];
+    let mut last_shown = None;
+    for (index, (marker, line)) in diff.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|changed_index| changed_index.abs_diff(index) <= DIFF_CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|last| last + 1 != index) {
+            out.push("...".to_string());
+        }
+        out.push(format!("{marker} {line}"));
+        last_shown = Some(index);
+    }
+
+    return out.join("\n");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/svg/basic_end_to_end.svg"
+    );
+
+    #[test]
+    fn test_normalise_svg() {
+        let svg = "<svg xmlns='http://www.w3.org/2000/svg' viewBox=\"0 0 100.0 100\">
+            <!-- A square -->
+            <rect x=\"25.0000001\" y=\"25\" width=\"50\" height=\"50.004\"
+                  stroke=\"rgb(0,0,0)\" stroke-width=\"5\" fill=\"transparent\" />
+            <text fill=\"#00ff00\" id=\"label2\" x=\"-0.001\">Hello</text>
+        </svg>";
+
+        assert_eq!(
+            normalise_svg(svg),
+            "<svg viewBox=\"0 0 100 100\" xmlns=\"http://www.w3.org/2000/svg\">
+<rect fill=\"transparent\" height=\"50\" stroke=\"rgb(0,0,0)\" stroke-width=\"5\" width=\"50\" x=\"25\" y=\"25\"/>
+<text fill=\"#00ff00\" id=\"label2\" x=\"0\">
+Hello
+</text>
+</svg>
+"
+        );
+    }
+
+    #[test]
+    fn test_normalise_numbers() {
+        assert_eq!(
+            normalise_numbers("M 1.005,2.499 L -3.5 .25"),
+            "M 1,2.5 L -3.5 0.25"
+        );
+        assert_eq!(
+            normalise_numbers("translate(10.126 -0.0001)"),
+            "translate(10.13 0)"
+        );
+        assert_eq!(normalise_numbers("#1e90ff"), "#1e90ff");
+    }
+
+    #[test]
+    fn test_basic_end_to_end() {
+        // Same picture as the golden file, written out differently
+        let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 100 100\"><rect \
+                   width=\"50.0\" height=\"49.999\" x=\"25\" y=\"25\" fill=\"transparent\" \
+                   stroke=\"rgb(0,0,0)\" stroke-width=\"5\"/></svg>";
+        assert_svg_snapshot(FIXTURE, svg);
+    }
+
+    #[test]
+    #[should_panic(expected = "SVG doesn't match")]
+    fn test_snapshot_mismatch() {
+        if env::var_os(UPDATE_ENV_VAR).is_some() {
+            panic!("SVG doesn't match, skipped so the fixture isn't overwritten");
+        }
+        let svg = "<svg viewBox=\"0 0 100 100\" xmlns=\"http://www.w3.org/2000/svg\">
+<rect fill=\"transparent\" height=\"50\" stroke=\"rgb(255,0,0)\" stroke-width=\"5\" width=\"50\" x=\"25\" y=\"25\"/>
+</svg>";
+        assert_svg_snapshot(FIXTURE, svg);
+    }
+
+    #[test]
+    fn test_line_diff() {
+        let expected = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj";
+        let actual = "a\nb\nc\nD\ne\nf\ng\nh\ni\nj\nk";
+        assert_eq!(
+            line_diff(expected, actual),
+            "  b\n  c\n- d\n+ D\n  e\n  f\n...\n  i\n  j\n+ k"
+        );
+    }
+}
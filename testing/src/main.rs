@@ -1,13 +1,31 @@
-use macroquad::prelude::*;
+use scene::{Colour, Position, Scene, Shape, Style, viewer};
 
-#[macroquad::main("Display")]
-async fn main() {
-    loop {
-        clear_background(WHITE);
+fn demo_scene() -> Scene {
+    let mut scene = Scene::new();
+    scene.layer("lines").add(
+        Shape::Line {
+            from: Position::new(40.0, 40.0),
+            to: Position::new(100.0, 200.0),
+        },
+        Style::stroke(Colour::BLUE, 15.0),
+    );
+    return scene;
+}
+
+fn main() {
+    macroquad::Window::new("Display", viewer::show(demo_scene()));
+}
 
-        draw_line(40.0, 40.0, 100.0, 200.0, 15.0, BLUE);
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        next_frame().await
+    #[test]
+    fn test_demo_scene() {
+        testing::assert_scene_snapshot(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/svg/demo_scene.svg"),
+            &demo_scene(),
+        );
     }
 }
-    
\ No newline at end of file